@@ -57,12 +57,12 @@ impl App for MyThing {
             }
         }
         draw.print(
-            &format!("C = ({}, {})", self.center_x, self.center_y),
+            &format!("c = ({}, {})", self.center_x, self.center_y),
             4,
             4,
             7,
         );
-        draw.print(&format!("R = {}", self.radius), 4, 12, 7);
+        draw.print(&format!("r = {}", self.radius), 4, 12, 7);
 
         for (x, y) in midpoint(self.center_x, self.center_y, self.radius) {
             draw.pset(x, y, 14);
@@ -1,6 +1,6 @@
-use std::f32::consts::{FRAC_1_SQRT_2, PI};
+use std::f32::consts::FRAC_1_SQRT_2;
 
-use runty8::{rnd, App, Button, Pico8};
+use runty8::{cos, flr, mid, sin, App, Button, Pico8};
 
 use std::iter::{Chain, Map};
 use std::slice;
@@ -52,21 +52,21 @@ impl App for GameState {
 
         let clouds = (0..=16)
             .map(|_| Cloud {
-                x: rnd(128.),
-                y: rnd(128.),
-                spd: 1. + rnd(4.),
-                w: 32. + rnd(32.),
+                x: pico8.rnd(128.),
+                y: pico8.rnd(128.),
+                spd: 1. + pico8.rnd(4.),
+                w: 32. + pico8.rnd(32.),
             })
             .collect();
 
         let particles = (0..=24)
             .map(|_| Particle {
-                x: rnd(128.),
-                y: rnd(128.),
-                s: flr(rnd(5.) / 4.),
-                spd: 0.25 + rnd(5.),
-                off: rnd(1.),
-                c: 6 + flr(0.5 + rnd(1.)),
+                x: pico8.rnd(128.),
+                y: pico8.rnd(128.),
+                s: flr(pico8.rnd(5.) / 4.),
+                spd: 0.25 + pico8.rnd(5.),
+                off: pico8.rnd(1.),
+                c: 6 + flr(0.5 + pico8.rnd(1.)),
             })
             .collect();
 
@@ -116,8 +116,7 @@ impl App for GameState {
             self.music_timer -= 1;
 
             if self.music_timer <= 0 {
-                // TODO: Implement `music` api
-                // music(10, 0, 7);
+                pico8.music_(10, 0, 7);
             }
         }
         if self.sfx_timer > 0 {
@@ -162,6 +161,7 @@ impl App for GameState {
                 &mut self.got_fruit,
                 self.room,
                 pico8,
+                &mut self.sfx_timer,
                 self.max_djump,
                 &mut self.has_dashed,
                 self.frames,
@@ -188,16 +188,18 @@ impl App for GameState {
         }
 
         if player_dead {
-            kill_player(self)
+            kill_player(self, pico8)
         } else if do_next_level {
             next_room(self, pico8);
         }
 
         if !is_title(self) {
-            self.clouds.iter_mut().for_each(Cloud::update);
+            self.clouds.iter_mut().for_each(|cloud| cloud.update(pico8));
         }
 
-        self.particles.iter_mut().for_each(Particle::update);
+        self.particles
+            .iter_mut()
+            .for_each(|particle| particle.update(pico8));
 
         // Update and remove dead dead_particles
         self.dead_particles.retain_mut(DeadParticle::update);
@@ -206,10 +208,10 @@ impl App for GameState {
         // start game
         if is_title(self) {
             if !self.start_game && (pico8.btn(K_JUMP) || pico8.btn(K_DASH)) {
-                // music(-1);
+                pico8.music(-1);
                 self.start_game_flash = 50;
                 self.start_game = true;
-                // sfx(38);
+                pico8.sfx(38);
             }
             if self.start_game {
                 self.start_game_flash -= 1;
@@ -223,10 +225,9 @@ impl App for GameState {
     fn draw(&mut self, draw: &mut Pico8) {
         draw.camera(0, 0);
         if self.effects.shake > 0 {
-            draw.camera(
-                (-2. + rnd(5.)).floor() as i32,
-                (-2. + rnd(5.)).floor() as i32,
-            );
+            let x = (-2. + draw.rnd(5.)).floor() as i32;
+            let y = (-2. + draw.rnd(5.)).floor() as i32;
+            draw.camera(x, y);
         }
 
         if self.freeze > 0 {
@@ -336,10 +337,10 @@ impl App for GameState {
 
         // Credits
         if is_title(self) {
-            draw.print("X+C", 58, 80, 5);
-            draw.print("MADDY THORSON", 41, 96, 5);
-            draw.print("NOEL BERRY", 46, 102, 5);
-            draw.print("PORTED BY JJANT", 34, 114, 5);
+            draw.print("x+c", 58, 80, 5);
+            draw.print("maddy thorson", 41, 96, 5);
+            draw.print("noel berry", 46, 102, 5);
+            draw.print("ported by jjant", 34, 114, 5);
         }
 
         if level_index(self.room) == 30 {
@@ -381,6 +382,8 @@ impl GameState {
                         &mut self.flash_bg,
                         &mut self.new_bg,
                         &mut self.pause_player,
+                        &mut self.sfx_timer,
+                        &mut self.music_timer,
                         self.seconds,
                         self.minutes,
                         self.deaths,
@@ -409,12 +412,12 @@ struct Cloud {
 }
 
 impl Cloud {
-    fn update(&mut self) {
+    fn update(&mut self, pico8: &mut Pico8) {
         self.x += self.spd;
 
         if self.x > 128. {
             self.x = -self.w;
-            self.y = rnd(128. - 8.);
+            self.y = pico8.rnd(128. - 8.);
         }
     }
 }
@@ -429,14 +432,14 @@ struct Particle {
 }
 
 impl Particle {
-    fn update(&mut self) {
+    fn update(&mut self, pico8: &mut Pico8) {
         self.x += self.spd;
 
         self.y += sin(self.off);
         self.off += (0.05_f32).min(self.spd / 32.);
         if self.x > 128. + 4. {
             self.x = -4.;
-            self.y = rnd(128.);
+            self.y = pico8.rnd(128.);
         }
     }
 }
@@ -465,13 +468,13 @@ struct Vec2<T> {
 }
 
 impl GameState {
-    fn begin_game(&mut self, pico8: &Pico8) {
+    fn begin_game(&mut self, pico8: &mut Pico8) {
         self.frames = 0;
         self.seconds = 0;
         self.minutes = 0;
         self.music_timer = 0;
         self.start_game = false;
-        // music(0, 0, 7);
+        pico8.music_(0, 0, 7);
         load_room(self, pico8, 0, 0);
     }
 }
@@ -483,14 +486,14 @@ const K_DOWN: Button = Button::Down;
 const K_JUMP: Button = Button::Circle;
 const K_DASH: Button = Button::Cross;
 
-fn title_screen(game_state: &mut GameState, pico8: &Pico8) {
+fn title_screen(game_state: &mut GameState, pico8: &mut Pico8) {
     game_state.got_fruit = vec![false; 30];
     game_state.frames = 0;
     game_state.deaths = 0;
     game_state.max_djump = 1;
     game_state.start_game = false;
     game_state.start_game_flash = 0;
-    // music(40,0,7)
+    pico8.music_(40, 0, 7);
     load_room(game_state, pico8, 7, 3)
 }
 
@@ -559,7 +562,8 @@ impl Player {
     fn update<T>(
         &mut self,
         this: &mut BaseObject,
-        state: &Pico8,
+        state: &mut Pico8,
+        sfx_timer: i32,
         objects: &mut T,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -605,6 +609,7 @@ impl Player {
         // -- smoke particles
         if on_ground && !self.was_on_ground {
             update_action.push_mut(Object::init(
+                state,
                 got_fruit,
                 room,
                 ObjectKind::Smoke,
@@ -630,7 +635,7 @@ impl Player {
             self.grace = 6;
 
             if self.djump < max_djump {
-                // psfx(54)
+                psfx(sfx_timer, state, 54);
                 self.djump = max_djump
             }
         } else if self.grace > 0 {
@@ -641,6 +646,7 @@ impl Player {
 
         if self.dash_time > 0 {
             update_action.push_mut(Object::init(
+                state,
                 got_fruit,
                 room,
                 ObjectKind::Smoke,
@@ -693,8 +699,9 @@ impl Player {
             {
                 maxfall = 0.4;
 
-                if rnd(10.) < 2. {
+                if state.rnd(10.) < 2. {
                     update_action.push_mut(Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Smoke,
@@ -713,11 +720,12 @@ impl Player {
             if self.jbuffer > 0 {
                 if self.grace > 0 {
                     // -- normal jump
-                    // psfx(1)
+                    psfx(sfx_timer, state, 1);
                     self.jbuffer = 0;
                     self.grace = 0;
                     this.spd.y = -2.0;
                     update_action.push_mut(Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Smoke,
@@ -736,13 +744,14 @@ impl Player {
                         0
                     };
                     if wall_dir != 0 {
-                        // psfx(2)
+                        psfx(sfx_timer, state, 2);
 
                         self.jbuffer = 0;
                         this.spd.y = -2.0;
                         this.spd.x = -wall_dir as f32 * (maxrun + 1.0);
                         if !(this.is_ice(state, room, wall_dir * 3, 0)) {
                             update_action.push_mut(Object::init(
+                                state,
                                 got_fruit,
                                 room,
                                 ObjectKind::Smoke,
@@ -762,6 +771,7 @@ impl Player {
 
             if self.djump > 0 && dash {
                 update_action.push_mut(Object::init(
+                    state,
                     got_fruit,
                     room,
                     ObjectKind::Smoke,
@@ -793,7 +803,7 @@ impl Player {
                     this.spd.y = 0.0;
                 }
 
-                // psfx(3);
+                psfx(sfx_timer, state, 3);
                 // *freeze = 2;
                 *shake = 6;
                 self.dash_target.x = 2.0 * sign(this.spd.x);
@@ -812,8 +822,9 @@ impl Player {
                     self.dash_accel.y *= FRAC_1_SQRT_2;
                 }
             } else if dash && self.djump <= 0 {
-                // psfx(9)
+                psfx(sfx_timer, state, 9);
                 update_action.push_mut(Object::init(
+                    state,
                     got_fruit,
                     room,
                     ObjectKind::Smoke,
@@ -853,7 +864,7 @@ impl Player {
 
     fn draw(&mut self, base_object: &mut BaseObject, draw: &mut Pico8, frames: i32) {
         if base_object.x < -1 || base_object.x > 121 {
-            base_object.x = clamp(base_object.x, -1, 121);
+            base_object.x = mid(base_object.x as f32, -1.0, 121.0) as i32;
             base_object.spd.x = 0.0;
         }
 
@@ -882,11 +893,9 @@ struct HairElement {
     size: i32,
 }
 
-#[allow(dead_code, unused_variables)]
-fn psfx(game_state: &GameState, num: i32) {
-    if game_state.sfx_timer <= 0 {
-        // TODO: Implement
-        // sfx(num)
+fn psfx(sfx_timer: i32, pico8: &mut Pico8, num: i32) {
+    if sfx_timer <= 0 {
+        pico8.sfx(num);
     }
 }
 
@@ -923,9 +932,12 @@ impl Spring {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update<T>(
         &mut self,
         this: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: i32,
         objects: &mut T,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -956,6 +968,7 @@ impl Spring {
                     self.delay = 10;
 
                     update_action.push_mut(Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Smoke,
@@ -964,7 +977,7 @@ impl Spring {
                         max_djump,
                     ));
 
-                    // psfx(8)
+                    psfx(sfx_timer, state, 8);
                 }
                 // -- breakable below us
                 let below = this.collide(objects.into_iter(), &ObjectKind::FallFloor, 0, 1);
@@ -975,6 +988,8 @@ impl Spring {
                     self.break_spring();
                     fall_floor.break_fall_floor::<VecObjects>(
                         fall_floor_object,
+                        state,
+                        sfx_timer,
                         &mut VecObjects { vec: others },
                         got_fruit,
                         room,
@@ -1148,13 +1163,13 @@ impl RoomTitle {
             draw.rectfill(24, 58, 104, 70, 0);
 
             if room.x == 3 && room.y == 1 {
-                draw.print("OLD SITE", 48, 62, 7);
+                draw.print("old site", 48, 62, 7);
             } else if level_index(room) == 30 {
-                draw.print("SUMMIT", 52, 62, 7);
+                draw.print("summit", 52, 62, 7);
             } else {
                 let level = (1 + level_index(room)) * 100;
                 let x = 52 + (if level < 1000 { 2 } else { 0 });
-                draw.print(&format!("{level} M"), x, 62, 7);
+                draw.print(&format!("{level} m"), x, 62, 7);
             }
 
             draw_time(seconds, minutes, draw, 4, 4);
@@ -1187,6 +1202,7 @@ fn got_fruit_for_room(got_fruit: &[bool], room: Vec2<i32>) -> bool {
 impl Object {
     #[must_use]
     fn init(
+        state: &mut Pico8,
         got_fruit: &[bool],
         room: Vec2<i32>,
         kind: ObjectKind,
@@ -1218,7 +1234,7 @@ impl Object {
             // TODO: figure out if we need an option here
             spr: kind.tile().map(|t| t as f32).unwrap_or(-42.),
         };
-        let object_type = ObjectKind::create(&kind, &mut base_object, got_fruit, max_djump);
+        let object_type = ObjectKind::create(&kind, state, &mut base_object, got_fruit, max_djump);
 
         Some(Self {
             base_object,
@@ -1233,7 +1249,8 @@ impl Object {
         effects: &mut GameEffects,
         got_fruit: &mut [bool],
         room: Vec2<i32>,
-        state: &Pico8,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         max_djump: i32,
         has_dashed: &mut bool,
         frames: i32,
@@ -1251,6 +1268,7 @@ impl Object {
             got_fruit,
             room,
             state,
+            sfx_timer,
             max_djump,
             has_dashed,
             frames,
@@ -1274,6 +1292,8 @@ impl Object {
         flash_bg: &mut bool,
         new_bg: &mut bool,
         pause_player: &mut bool,
+        sfx_timer: &mut i32,
+        music_timer: &mut i32,
         seconds: i32,
         minutes: i32,
         deaths: i32,
@@ -1320,6 +1340,7 @@ impl Object {
                     max_djump,
                     freeze,
                     shake,
+                    music_timer,
                     frames,
                 )
             }
@@ -1327,6 +1348,7 @@ impl Object {
             ObjectType::Flag(flag) => flag.draw(
                 &mut self.base_object,
                 draw,
+                sfx_timer,
                 objects,
                 frames,
                 seconds,
@@ -1534,7 +1556,8 @@ impl ObjectType {
         effects: &mut GameEffects,
         got_fruit: &mut [bool],
         room: Vec2<i32>,
-        state: &Pico8,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         max_djump: i32,
         has_dashed: &mut bool,
         frames: i32,
@@ -1547,7 +1570,7 @@ impl ObjectType {
     {
         match self {
             ObjectType::PlayerSpawn(player_spawn) => {
-                player_spawn.update(base_object, effects, got_fruit, room, max_djump)
+                player_spawn.update(base_object, state, effects, got_fruit, room, max_djump)
             }
             ObjectType::Smoke => Smoke::update(base_object),
             ObjectType::Platform(platform) => {
@@ -1557,6 +1580,7 @@ impl ObjectType {
             ObjectType::Player(player) => player.update(
                 base_object,
                 state,
+                *sfx_timer,
                 other_objects,
                 got_fruit,
                 room,
@@ -1567,34 +1591,79 @@ impl ObjectType {
                 freeze,
             ),
             ObjectType::LifeUp(life_up) => life_up.update(),
-            ObjectType::Fruit(fruit) => {
-                fruit.update(base_object, other_objects, got_fruit, room, max_djump)
-            }
-            ObjectType::FakeWall => {
-                FakeWall::update(base_object, other_objects, got_fruit, room, max_djump)
-            }
-            ObjectType::FallFloor(fall_floor) => {
-                fall_floor.update(base_object, other_objects, got_fruit, room, max_djump)
-            }
-            ObjectType::Key => Key::update(base_object, other_objects, frames, has_key),
+            ObjectType::Fruit(fruit) => fruit.update(
+                base_object,
+                state,
+                sfx_timer,
+                other_objects,
+                got_fruit,
+                room,
+                max_djump,
+            ),
+            ObjectType::FakeWall => FakeWall::update(
+                base_object,
+                state,
+                sfx_timer,
+                other_objects,
+                got_fruit,
+                room,
+                max_djump,
+            ),
+            ObjectType::FallFloor(fall_floor) => fall_floor.update(
+                base_object,
+                state,
+                *sfx_timer,
+                other_objects,
+                got_fruit,
+                room,
+                max_djump,
+            ),
+            ObjectType::Key => Key::update(
+                base_object,
+                state,
+                sfx_timer,
+                other_objects,
+                frames,
+                has_key,
+            ),
             ObjectType::RoomTitle(rt) => rt.update(),
-            ObjectType::Spring(spring) => {
-                spring.update(base_object, other_objects, got_fruit, room, max_djump)
-            }
+            ObjectType::Spring(spring) => spring.update(
+                base_object,
+                state,
+                *sfx_timer,
+                other_objects,
+                got_fruit,
+                room,
+                max_djump,
+            ),
             ObjectType::FlyFruit(fly_fruit) => fly_fruit.update(
                 base_object,
+                state,
+                sfx_timer,
                 other_objects,
                 got_fruit,
                 room,
                 max_djump,
                 *has_dashed,
             ),
-            ObjectType::Chest(chest) => {
-                chest.update(base_object, got_fruit, room, *has_key, max_djump)
-            }
-            ObjectType::Balloon(balloon) => {
-                balloon.update(base_object, other_objects, got_fruit, room, max_djump)
-            }
+            ObjectType::Chest(chest) => chest.update(
+                base_object,
+                state,
+                sfx_timer,
+                got_fruit,
+                room,
+                *has_key,
+                max_djump,
+            ),
+            ObjectType::Balloon(balloon) => balloon.update(
+                base_object,
+                state,
+                *sfx_timer,
+                other_objects,
+                got_fruit,
+                room,
+                max_djump,
+            ),
             ObjectType::Orb(_) => UpdateAction::noop(),
             ObjectType::Message(_) => UpdateAction::noop(),
             ObjectType::Flag(_) => UpdateAction::noop(),
@@ -1602,9 +1671,9 @@ impl ObjectType {
     }
 }
 
-fn kill_player(game_state: &mut GameState) {
+fn kill_player(game_state: &mut GameState, pico8: &mut Pico8) {
     game_state.sfx_timer = 12;
-    // sfx(0);
+    pico8.sfx(0);
     game_state.deaths += 1;
     game_state.effects.shake = 10;
 
@@ -1643,18 +1712,18 @@ fn restart_room(game_state: &mut GameState) {
     game_state.delay_restart = 15;
 }
 
-fn next_room(game_state: &mut GameState, state: &Pico8) {
+fn next_room(game_state: &mut GameState, state: &mut Pico8) {
     let room = game_state.room;
 
     #[allow(clippy::if_same_then_else)]
     if room.x == 2 && room.y == 1 {
-        // music(30, 500, 7)
+        state.music_(30, 500, 7);
     } else if room.x == 3 && room.y == 1 {
-        // music(20, 500, 7)
+        state.music_(20, 500, 7);
     } else if room.x == 4 && room.y == 2 {
-        // music(30, 500, 7)
+        state.music_(30, 500, 7);
     } else if room.x == 5 && room.y == 3 {
-        // music(30, 500, 7)
+        state.music_(30, 500, 7);
     }
     if room.x == 7 {
         load_room(game_state, state, 0, room.y + 1);
@@ -1663,7 +1732,7 @@ fn next_room(game_state: &mut GameState, state: &Pico8) {
     }
 }
 
-fn load_room(game_state: &mut GameState, state: &Pico8, x: i32, y: i32) {
+fn load_room(game_state: &mut GameState, state: &mut Pico8, x: i32, y: i32) {
     game_state.has_dashed = false;
     game_state.has_key = false;
 
@@ -1680,6 +1749,7 @@ fn load_room(game_state: &mut GameState, state: &Pico8, x: i32, y: i32) {
             let tile = state.mget(game_state.room.x * 16 + tx, game_state.room.y * 16 + ty);
             if tile == 11 {
                 let mut platform = Object::init(
+                    state,
                     &game_state.got_fruit,
                     game_state.room,
                     ObjectKind::Platform,
@@ -1692,6 +1762,7 @@ fn load_room(game_state: &mut GameState, state: &Pico8, x: i32, y: i32) {
                 game_state.objects.push(platform);
             } else if tile == 12 {
                 let mut platform = Object::init(
+                    state,
                     &game_state.got_fruit,
                     game_state.room,
                     ObjectKind::Platform,
@@ -1706,6 +1777,7 @@ fn load_room(game_state: &mut GameState, state: &Pico8, x: i32, y: i32) {
                 for kind in ObjectKind::TYPES.iter().copied() {
                     if kind.tile() == Some(tile.into()) {
                         if let Some(object) = Object::init(
+                            state,
                             &game_state.got_fruit,
                             game_state.room,
                             kind,
@@ -1723,6 +1795,7 @@ fn load_room(game_state: &mut GameState, state: &Pico8, x: i32, y: i32) {
 
     if !is_title(game_state) {
         if let Some(object) = Object::init(
+            state,
             &game_state.got_fruit,
             game_state.room,
             ObjectKind::RoomTitle,
@@ -1764,10 +1837,6 @@ fn draw_time(seconds: i32, minutes: i32, draw: &mut Pico8, x: i32, y: i32) {
 // -- helper functions --
 // ----------------------
 
-fn clamp(val: i32, a: i32, b: i32) -> i32 {
-    a.max(b.min(val))
-}
-
 fn appr(val: f32, target: f32, amount: f32) -> f32 {
     if val > target {
         f32::max(val - amount, target)
@@ -1776,8 +1845,8 @@ fn appr(val: f32, target: f32, amount: f32) -> f32 {
     }
 }
 
-fn maybe() -> bool {
-    rnd(1.0) > 0.5
+fn maybe(state: &mut Pico8) -> bool {
+    state.rnd(1.0) > 0.5
 }
 
 fn ice_at(state: &Pico8, room: Vec2<i32>, x: i32, y: i32, w: i32, h: i32) -> bool {
@@ -1878,14 +1947,14 @@ impl Platform {
 struct Smoke;
 
 impl Smoke {
-    fn init(base_object: &mut BaseObject) {
+    fn init(state: &mut Pico8, base_object: &mut BaseObject) {
         base_object.spr = 29.;
         base_object.spd.y = -0.1;
-        base_object.spd.x = 0.3 + rnd(0.2);
-        base_object.x += -1 + flr(rnd(2.));
-        base_object.y += -1 + flr(rnd(2.));
-        base_object.flip.x = maybe();
-        base_object.flip.y = maybe();
+        base_object.spd.x = 0.3 + state.rnd(0.2);
+        base_object.x += -1 + flr(state.rnd(2.));
+        base_object.y += -1 + flr(state.rnd(2.));
+        base_object.flip.x = maybe(state);
+        base_object.flip.y = maybe(state);
         base_object.is_solid = false;
     }
 
@@ -1910,9 +1979,12 @@ impl Fruit {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update<T>(
         &mut self,
         base_object: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         other_objects: &mut T,
         got_fruit: &mut [bool],
         room: Vec2<i32>,
@@ -1927,11 +1999,12 @@ impl Fruit {
             let (_, player) = hit.to_player_mut().unwrap();
 
             player.djump = max_djump;
-            // sfx_timer=20
-            // sfx(13)
+            *sfx_timer = 20;
+            state.sfx(13);
             got_fruit[level_index(room)] = true;
 
             UpdateAction::noop().destroy().push(Object::init(
+                state,
                 got_fruit,
                 room,
                 ObjectKind::LifeUp,
@@ -1995,16 +2068,19 @@ impl ObjectKind {
 
     fn create(
         &self,
+        state: &mut Pico8,
         base_object: &mut BaseObject,
         got_fruit: &[bool],
         max_djump: i32,
     ) -> ObjectType {
         match self {
-            ObjectKind::PlayerSpawn => ObjectType::PlayerSpawn(PlayerSpawn::init(base_object)),
+            ObjectKind::PlayerSpawn => {
+                ObjectType::PlayerSpawn(PlayerSpawn::init(state, base_object))
+            }
             ObjectKind::Player => ObjectType::Player(Player::init(base_object, max_djump)),
 
             ObjectKind::Spring => ObjectType::Spring(Spring::init()),
-            ObjectKind::Balloon => ObjectType::Balloon(Balloon::init(base_object)),
+            ObjectKind::Balloon => ObjectType::Balloon(Balloon::init(state, base_object)),
             ObjectKind::FallFloor => ObjectType::FallFloor(FallFloor::init()),
             ObjectKind::Fruit => ObjectType::Fruit(Fruit::init(base_object)),
             ObjectKind::FlyFruit => ObjectType::FlyFruit(FlyFruit::init(base_object)),
@@ -2017,7 +2093,7 @@ impl ObjectKind {
             ObjectKind::RoomTitle => ObjectType::RoomTitle(RoomTitle::init()),
             ObjectKind::Platform => ObjectType::Platform(Platform::init(base_object)),
             ObjectKind::Smoke => {
-                Smoke::init(base_object);
+                Smoke::init(state, base_object);
                 ObjectType::Smoke
             }
             ObjectKind::FakeWall => ObjectType::FakeWall,
@@ -2149,11 +2225,10 @@ impl UpdateAction {
 }
 
 impl PlayerSpawn {
-    fn init(base_object: &mut BaseObject) -> Self {
+    fn init(state: &mut Pico8, base_object: &mut BaseObject) -> Self {
         use PlayerSpawnState::*;
 
-        // TODO: Implement sound api
-        // sfx(4)
+        state.sfx(4);
         base_object.spr = 3.0;
         let target = Vec2 {
             x: base_object.x,
@@ -2174,6 +2249,7 @@ impl PlayerSpawn {
     fn update(
         &mut self,
         base_object: &mut BaseObject,
+        state: &mut Pico8,
         effects: &mut GameEffects,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -2205,6 +2281,7 @@ impl PlayerSpawn {
 
                     effects.shake = 5;
                     update_action.push_mut(Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Smoke,
@@ -2213,7 +2290,7 @@ impl PlayerSpawn {
                         max_djump,
                     ));
 
-                    // sfx(5);
+                    state.sfx(5);
                 };
 
                 update_action
@@ -2226,6 +2303,7 @@ impl PlayerSpawn {
 
                 if should_destroy {
                     let player = Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Player,
@@ -2264,6 +2342,8 @@ struct FakeWall;
 impl FakeWall {
     fn update<T>(
         base_object: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         other_objects: &mut T,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -2289,13 +2369,13 @@ impl FakeWall {
                     hit_object.base_object.spd.x = -sign(hit_object.base_object.spd.x) * 1.5;
                     hit_object.base_object.spd.y = -1.5;
                     player.dash_time = -1;
-                    // TODO:
-                    //     sfx_timer=20
-                    //     sfx(16)
+                    *sfx_timer = 20;
+                    state.sfx(16);
 
                     update_action = update_action
                         .destroy()
                         .push(Object::init(
+                            state,
                             got_fruit,
                             room,
                             ObjectKind::Smoke,
@@ -2304,6 +2384,7 @@ impl FakeWall {
                             max_djump,
                         ))
                         .push(Object::init(
+                            state,
                             got_fruit,
                             room,
                             ObjectKind::Smoke,
@@ -2312,6 +2393,7 @@ impl FakeWall {
                             max_djump,
                         ))
                         .push(Object::init(
+                            state,
                             got_fruit,
                             room,
                             ObjectKind::Smoke,
@@ -2320,6 +2402,7 @@ impl FakeWall {
                             max_djump,
                         ))
                         .push(Object::init(
+                            state,
                             got_fruit,
                             room,
                             ObjectKind::Smoke,
@@ -2328,6 +2411,7 @@ impl FakeWall {
                             max_djump,
                         ))
                         .push(Object::init(
+                            state,
                             got_fruit,
                             room,
                             ObjectKind::Fruit,
@@ -2399,22 +2483,6 @@ fn sign(f: f32) -> f32 {
     }
 }
 
-fn turn_to_rad(turns: f32) -> f32 {
-    turns * 2.0 * PI
-}
-
-fn sin(turns: f32) -> f32 {
-    turn_to_rad(turns).sin()
-}
-
-fn cos(turns: f32) -> f32 {
-    turn_to_rad(turns).cos()
-}
-
-fn flr(f: f32) -> i32 {
-    f.floor() as i32
-}
-
 #[derive(Clone, Copy, Debug)]
 struct FlyFruit {
     start: i32,
@@ -2435,9 +2503,12 @@ impl FlyFruit {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update<T>(
         &mut self,
         this: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         objects: &mut T,
         got_fruit: &mut [bool],
         room: Vec2<i32>,
@@ -2454,8 +2525,8 @@ impl FlyFruit {
                 self.sfx_delay -= 1;
 
                 if self.sfx_delay <= 0 {
-                    // sfx_timer = 20;
-                    // sfx(14);
+                    *sfx_timer = 20;
+                    state.sfx(14);
                 }
             }
             this.spd.y = appr(this.spd.y, -3.5, 0.25);
@@ -2474,10 +2545,11 @@ impl FlyFruit {
         if let Some((_, hit)) = this.collide(objects.into_iter(), &ObjectKind::Player, 0, 0) {
             let (_, player) = hit.to_player_mut().unwrap();
             player.djump = max_djump;
-            // sfx_timer = 20;
-            // sfx(13);
+            *sfx_timer = 20;
+            state.sfx(13);
             got_fruit[1 + level_index(room)] = true;
             update_action.push_mut(Object::init(
+                state,
                 got_fruit,
                 room,
                 ObjectKind::LifeUp,
@@ -2539,9 +2611,12 @@ impl FallFloor {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update<T>(
         &mut self,
         this: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: i32,
         objects: &mut T,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -2559,6 +2634,8 @@ impl FallFloor {
                 {
                     self.break_fall_floor(
                         this,
+                        state,
+                        sfx_timer,
                         objects,
                         got_fruit,
                         room,
@@ -2580,11 +2657,12 @@ impl FallFloor {
                 self.delay -= 1;
 
                 if self.delay <= 0 && !this.check(objects.into_iter(), &ObjectKind::Player, 0, 0) {
-                    // psfx(7)
+                    psfx(sfx_timer, state, 7);
                     self.state = FallFloorState::Idling;
                     this.collideable = true;
 
                     update_action.push_mut(Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Smoke,
@@ -2608,9 +2686,12 @@ impl FallFloor {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn break_fall_floor<T>(
         &mut self,
         this: &BaseObject,
+        state: &mut Pico8,
+        sfx_timer: i32,
         objects: &mut T,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -2621,11 +2702,12 @@ impl FallFloor {
     {
         match self.state {
             FallFloorState::Idling => {
-                // psfx(15);
+                psfx(sfx_timer, state, 15);
                 self.state = FallFloorState::Shaking;
                 self.delay = 15; // --how long until it falls
 
                 update_action.push_mut(Object::init(
+                    state,
                     got_fruit,
                     room,
                     ObjectKind::Smoke,
@@ -2653,6 +2735,8 @@ struct Key;
 impl Key {
     fn update<T>(
         this: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         objects: &mut T,
         frames: i32,
         has_key: &mut bool,
@@ -2669,8 +2753,8 @@ impl Key {
             this.flip.x = !this.flip.x;
         }
         if this.check(objects.into_iter(), &ObjectKind::Player, 0, 0) {
-            // sfx(23);
-            // sfx_timer = 10;
+            state.sfx(23);
+            *sfx_timer = 10;
             update_action.destroy_if_mut(true);
             *has_key = true;
         }
@@ -2694,9 +2778,12 @@ impl Chest {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update(
         &mut self,
         this: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: &mut i32,
         got_fruit: &[bool],
         room: Vec2<i32>,
         has_key: bool,
@@ -2705,11 +2792,12 @@ impl Chest {
         let mut update_action = UpdateAction::noop();
         if has_key {
             self.timer -= 1;
-            this.x = self.start - 1 + flr(rnd(3.0));
+            this.x = self.start - 1 + flr(state.rnd(3.0));
             if self.timer <= 0 {
-                // sfx_timer = 20;
-                // sfx(16);
+                *sfx_timer = 20;
+                state.sfx(16);
                 update_action.push_mut(Object::init(
+                    state,
                     got_fruit,
                     room,
                     ObjectKind::Fruit,
@@ -2733,7 +2821,7 @@ struct Balloon {
 }
 
 impl Balloon {
-    fn init(this: &mut BaseObject) -> Self {
+    fn init(state: &mut Pico8, this: &mut BaseObject) -> Self {
         this.hitbox = Hitbox {
             x: -1,
             y: -1,
@@ -2742,15 +2830,18 @@ impl Balloon {
         };
 
         Self {
-            offset: rnd(1.0),
+            offset: state.rnd(1.0),
             timer: 0,
             start: this.y,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update<T>(
         &mut self,
         this: &mut BaseObject,
+        state: &mut Pico8,
+        sfx_timer: i32,
         objects: &mut T,
         got_fruit: &[bool],
         room: Vec2<i32>,
@@ -2768,8 +2859,9 @@ impl Balloon {
             if let Some((_, hit)) = this.collide(objects.into_iter(), &ObjectKind::Player, 0, 0) {
                 let (_, player) = hit.to_player_mut().unwrap();
                 if player.djump < max_djump {
-                    // psfx(6);
+                    psfx(sfx_timer, state, 6);
                     update_action.push_mut(Object::init(
+                        state,
                         got_fruit,
                         room,
                         ObjectKind::Smoke,
@@ -2785,8 +2877,9 @@ impl Balloon {
         } else if self.timer > 0 {
             self.timer -= 1;
         } else {
-            // psfx(7);
+            psfx(sfx_timer, state, 7);
             update_action.push_mut(Object::init(
+                state,
                 got_fruit,
                 room,
                 ObjectKind::Smoke,
@@ -2870,13 +2963,14 @@ impl BigChest {
                     );
 
                     if is_solid {
-                        // music(-1, 500, 7);
-                        // sfx(37);
+                        draw.music_(-1, 500, 7);
+                        draw.sfx(37);
                         *pause_player = true;
                         base.spd.x = 0.0;
                         base.spd.y = 0.0;
 
                         update_action.push_mut(Object::init(
+                            draw,
                             got_fruit,
                             room,
                             ObjectKind::Smoke,
@@ -2885,6 +2979,7 @@ impl BigChest {
                             max_djump,
                         ));
                         update_action.push_mut(Object::init(
+                            draw,
                             got_fruit,
                             room,
                             ObjectKind::Smoke,
@@ -2908,10 +3003,10 @@ impl BigChest {
                 *flash_bg = true;
                 if *timer <= 45 && particles.len() < 50 {
                     particles.push(BigChestParticle {
-                        x: 1 + flr(rnd(14.0)),
+                        x: 1 + flr(draw.rnd(14.0)),
                         y: 0,
-                        h: 32 + flr(rnd(32.0)),
-                        spd: 8 + flr(rnd(8.0)),
+                        h: 32 + flr(draw.rnd(32.0)),
+                        spd: 8 + flr(draw.rnd(8.0)),
                     })
                 }
 
@@ -2931,6 +3026,7 @@ impl BigChest {
                     *new_bg = true;
 
                     update_action.push_mut(Object::init(
+                        draw,
                         got_fruit,
                         room,
                         ObjectKind::Orb,
@@ -3025,6 +3121,7 @@ impl Orb {
         max_djump: &mut i32,
         freeze: &mut i32,
         shake: &mut i32,
+        music_timer: &mut i32,
         frames: i32,
     ) -> UpdateAction
     where
@@ -3036,8 +3133,8 @@ impl Orb {
         if this.spd.y == 0.0 {
             if let Some((_, hit)) = this.collide(objects.into_iter(), &ObjectKind::Player, 0, 0) {
                 let (_, player) = hit.to_player_mut().unwrap();
-                // music_timer = 45;
-                // sfx(51);
+                *music_timer = 45;
+                draw.sfx(51);
                 *freeze = 10;
                 *shake = 10;
                 update_action.destroy_if_mut(true);
@@ -3082,15 +3179,14 @@ impl Message {
     where
         for<'b> &'b mut T: IntoIterator<Item = &'b mut Object>,
     {
-        // TODO: Fix font in runty8, this shouldn't need to be uppercase here.
-        const TEXT: &str = "-- CELESTE MOUNTAIN --#THIS MEMORIAL TO THOSE# PERISHED ON THE CLIMB";
+        const TEXT: &str = "-- celeste mountain --#this memorial to those# perished on the climb";
 
         if this.check(objects.into_iter(), &ObjectKind::Player, 4, 0) {
             if self.index < TEXT.len() as f32 {
                 self.index += 0.5;
                 if self.index >= (self.last + 1) as f32 {
                     self.last += 1;
-                    //  sfx(35)
+                    draw.sfx(35);
                 }
             }
 
@@ -3143,6 +3239,7 @@ impl Flag {
         &mut self,
         this: &mut BaseObject,
         draw: &mut Pico8,
+        sfx_timer: &mut i32,
         objects: &mut T,
         frames: i32,
         seconds: i32,
@@ -3157,12 +3254,12 @@ impl Flag {
         if self.show {
             draw.rectfill(32, 2, 96, 31, 0);
             draw.spr(26, 55, 6);
-            draw.print(&format!("X{}", self.score), 64, 9, 7);
+            draw.print(&format!("x{}", self.score), 64, 9, 7);
             draw_time(seconds, minutes, draw, 49, 16);
-            draw.print(&format!("DEATHS:{deaths}"), 48, 24, 7);
+            draw.print(&format!("deaths:{deaths}"), 48, 24, 7);
         } else if this.check(objects.into_iter(), &ObjectKind::Player, 0, 0) {
-            // sfx(55);
-            // sfx_timer = 30;
+            draw.sfx(55);
+            *sfx_timer = 30;
             self.show = true;
         }
     }
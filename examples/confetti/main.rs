@@ -25,8 +25,8 @@ impl App for Confetti {
 
         if state.btn(Button::Mouse) {
             for _ in 0..10 {
-                self.particles
-                    .push(Particle::new(self.mouse_x as f32, self.mouse_y as f32));
+                let particle = Particle::new(state, self.mouse_x as f32, self.mouse_y as f32);
+                self.particles.push(particle);
             }
         }
 
@@ -47,18 +47,8 @@ impl App for Confetti {
 
         let text_x = 3;
         let text_y = 3;
-        draw_context.print(
-            &"click and drag to ".to_ascii_uppercase(),
-            text_x,
-            text_y,
-            7,
-        );
-        draw_context.print(
-            &"throw confetti".to_ascii_uppercase(),
-            text_x,
-            text_y + 7,
-            7,
-        );
+        draw_context.print("click and drag to ", text_x, text_y, 7);
+        draw_context.print("throw confetti", text_x, text_y + 7, 7);
 
         for particle in self.particles.iter() {
             particle.draw(draw_context)
@@ -79,12 +69,12 @@ struct Particle {
 }
 
 impl Particle {
-    fn new(x: f32, y: f32) -> Self {
-        let x = rand_between(-2.0, 2.0) + x;
-        let y = rand_between(-2.0, 2.0) + y;
-        let vx = rand_between(-15.0, 15.0) / 50.0;
-        let vy = rand_between(-50.0, 10.0) / 50.0;
-        let ttl = rand_between(10.0, 70.0) as i32;
+    fn new(state: &mut Pico8, x: f32, y: f32) -> Self {
+        let x = rand_between(state, -2.0, 2.0) + x;
+        let y = rand_between(state, -2.0, 2.0) + y;
+        let vx = rand_between(state, -15.0, 15.0) / 50.0;
+        let vy = rand_between(state, -50.0, 10.0) / 50.0;
+        let ttl = rand_between(state, 10.0, 70.0) as i32;
 
         Self {
             x,
@@ -93,7 +83,7 @@ impl Particle {
             vy,
             ay: 0.05,
             ttl,
-            color: rand_between(1.0, 16.0) as u8,
+            color: rand_between(state, 1.0, 16.0) as u8,
         }
     }
 
@@ -109,6 +99,6 @@ impl Particle {
     }
 }
 
-fn rand_between(min: f32, max: f32) -> f32 {
-    min + runty8::rnd(max - min)
+fn rand_between(state: &mut Pico8, min: f32, max: f32) -> f32 {
+    min + state.rnd(max - min)
 }
@@ -27,7 +27,7 @@ impl App for ExampleApp {
     fn draw(&mut self, draw_context: &mut Pico8) {
         draw_context.cls(0);
         draw_context.print(
-            &format!("X={} Y={} YC={}", self.x / 100, self.y / 100, self.yc),
+            &format!("x={} y={} yc={}", self.x / 100, self.y / 100, self.yc),
             0,
             0,
             6,
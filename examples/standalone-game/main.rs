@@ -75,7 +75,7 @@ impl App for Game {
 
             pico8.rectfill(x * 4, y * 4, x * 4 + 3, y * 4 + 3, i as u8);
         }
-        pico8.print("USE ARROW KEYS TO MOVE", 8, 8, 12);
+        pico8.print("use arrow keys to move", 8, 8, 12);
         pico8.rectfill(
             self.x,
             self.y,
@@ -0,0 +1,507 @@
+//! Pico8-style sound: sound effects synthesized on 4 mixing channels,
+//! and a music sequencer playing patterns of them.
+
+mod music;
+mod output;
+mod sfx;
+mod synth;
+
+pub use music::{Music, Pattern};
+pub use output::{AudioOutput, MemoryOutput, WavOutput};
+pub use sfx::{Effect, Note, Sfx, SoundEffects, Waveform};
+
+use music::{Sequencer, Step};
+use synth::Voice;
+
+/// Samples per second produced by [`Audio`], same as pico8.
+pub const SAMPLE_RATE: u32 = 22050;
+
+/// How many sound effects can play at the same time.
+pub const CHANNELS: usize = 4;
+
+/// Pico8's sound state: what each channel is playing.
+#[derive(Debug)]
+pub struct Audio {
+    channels: [Option<Voice>; CHANNELS],
+    music: Option<Sequencer>,
+    /// Bitmask of the channels playing the current music pattern.
+    music_channels: u8,
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        Self {
+            channels: Default::default(),
+            music: None,
+            music_channels: 0,
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Sfx>
+    pub(crate) fn sfx(&mut self, n: i32, channel: i32, offset: i32, length: i32) {
+        match (n, channel) {
+            (-1, -1) => {
+                for channel in 0..CHANNELS {
+                    self.stop_channel(channel);
+                }
+            }
+            (-1, _) => {
+                if let Ok(channel) = usize::try_from(channel) {
+                    self.stop_channel(channel);
+                }
+            }
+            (-2, -1) => self.voices_mut().for_each(Voice::release),
+            (-2, _) => {
+                if let Some(Some(voice)) = self.channel_mut(channel) {
+                    voice.release();
+                }
+            }
+            (n, -2) => {
+                for channel in 0..CHANNELS {
+                    if self.playing_sfx(channel).map(|sfx| sfx as i32) == Some(n) {
+                        self.stop_channel(channel);
+                    }
+                }
+            }
+            (n, channel) if n >= 0 => {
+                let channel = if channel < 0 {
+                    self.free_channel()
+                } else {
+                    channel as usize
+                };
+
+                let offset = offset.clamp(0, Sfx::NOTE_COUNT as i32) as usize;
+                let length = length.clamp(0, Sfx::NOTE_COUNT as i32) as usize;
+                if channel < CHANNELS {
+                    self.stop_channel(channel);
+                    self.channels[channel] = Some(Voice::new(n as usize, offset, length));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Music>
+    pub(crate) fn music(&mut self, n: i32, fade_millis: i32, channel_mask: u8) {
+        if n < 0 {
+            match &mut self.music {
+                Some(sequencer) if fade_millis > 0 => sequencer.fade_out(fade_millis),
+                _ => self.stop_music(),
+            }
+        } else {
+            self.stop_music();
+            self.music = Some(Sequencer::new(n as usize, fade_millis, channel_mask));
+        }
+    }
+
+    /// Mixes the next `samples.len()` samples of every channel into `samples`.
+    pub fn render(&mut self, sound_effects: &SoundEffects, music: &Music, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            self.step_music(sound_effects, music);
+
+            let music_volume = self.music.as_ref().map_or(1.0, Sequencer::volume);
+            let mut mixed = 0.0;
+
+            for (index, channel) in self.channels.iter_mut().enumerate() {
+                match channel
+                    .as_mut()
+                    .map(|voice| voice.next_sample(sound_effects))
+                {
+                    Some(Some(voice_sample)) if self.music_channels & (1 << index) != 0 => {
+                        mixed += voice_sample * music_volume
+                    }
+                    Some(Some(voice_sample)) => mixed += voice_sample,
+                    Some(None) => {
+                        *channel = None;
+                        self.music_channels &= !(1 << index);
+                    }
+                    None => {}
+                }
+            }
+
+            *sample = mixed.clamp(-1.0, 1.0);
+        }
+    }
+
+    /// Renders the next `len` samples into `output`.
+    pub fn render_to(
+        &mut self,
+        sound_effects: &SoundEffects,
+        music: &Music,
+        len: usize,
+        output: &mut dyn AudioOutput,
+    ) -> std::io::Result<()> {
+        let mut samples = vec![0.0; len];
+        self.render(sound_effects, music, &mut samples);
+
+        output.write(&samples)
+    }
+
+    /// Music pattern being played, if any (pico8's `stat(54)`).
+    pub fn playing_pattern(&self) -> Option<usize> {
+        self.music.as_ref().map(|sequencer| sequencer.pattern)
+    }
+
+    /// Sound effect playing on `channel`, if any (pico8's `stat(46..=49)`).
+    pub fn playing_sfx(&self, channel: usize) -> Option<usize> {
+        self.channels.get(channel)?.as_ref().map(|voice| voice.sfx)
+    }
+
+    /// Index of the note playing on `channel`, if any (pico8's `stat(50..=53)`).
+    pub fn playing_note(&self, channel: usize) -> Option<usize> {
+        self.channels.get(channel)?.as_ref().map(|voice| voice.note)
+    }
+
+    fn step_music(&mut self, sound_effects: &SoundEffects, music: &Music) {
+        let step = match &mut self.music {
+            Some(sequencer) => sequencer.step(music, sound_effects),
+            None => return,
+        };
+
+        match step {
+            Step::Continue => {}
+            Step::Start(pattern_channels) => {
+                for (channel, sfx) in pattern_channels.into_iter().enumerate() {
+                    if self.music_channels & (1 << channel) != 0 {
+                        self.stop_channel(channel);
+                    }
+
+                    if let Some(sfx) = sfx {
+                        let voice = Voice::new(sfx as usize, 0, Sfx::NOTE_COUNT);
+                        self.channels[channel] = Some(voice);
+                        self.music_channels |= 1 << channel;
+                    }
+                }
+            }
+            Step::Stop => self.stop_music(),
+        }
+    }
+
+    fn stop_music(&mut self) {
+        for channel in 0..CHANNELS {
+            if self.music_channels & (1 << channel) != 0 {
+                self.stop_channel(channel);
+            }
+        }
+        self.music = None;
+    }
+
+    fn stop_channel(&mut self, channel: usize) {
+        if let Some(voice) = self.channels.get_mut(channel) {
+            *voice = None;
+            self.music_channels &= !(1 << channel);
+        }
+    }
+
+    fn channel_mut(&mut self, channel: i32) -> Option<&mut Option<Voice>> {
+        self.channels.get_mut(usize::try_from(channel).ok()?)
+    }
+
+    fn voices_mut(&mut self) -> impl Iterator<Item = &mut Voice> {
+        self.channels.iter_mut().flatten()
+    }
+
+    /// The first idle channel or, if all of them are busy,
+    /// the one that has been playing for the longest.
+    ///
+    /// Channels reserved for music, or playing it, are avoided if possible.
+    fn free_channel(&self) -> usize {
+        let reserved = self.music_channels
+            | self
+                .music
+                .as_ref()
+                .map_or(0, |sequencer| sequencer.channel_mask);
+
+        let unreserved = (0..CHANNELS).filter(|channel| reserved & (1 << channel) == 0);
+        let candidates: Vec<usize> = if unreserved.clone().count() > 0 {
+            unreserved.collect()
+        } else {
+            (0..CHANNELS).collect()
+        };
+
+        candidates
+            .iter()
+            .copied()
+            .find(|&channel| self.channels[channel].is_none())
+            .or_else(|| {
+                candidates.iter().copied().max_by_key(|&channel| {
+                    self.channels[channel].as_ref().map(|voice| voice.elapsed)
+                })
+            })
+            .unwrap_or(0)
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::synth::TICK_SAMPLES;
+    use super::*;
+
+    fn sound_effects(notes: &[Note], speed: u8, loop_start: u8, loop_end: u8) -> SoundEffects {
+        let mut sound_effects = SoundEffects::new();
+        let sfx = sound_effects.get_mut(3).unwrap();
+        sfx.notes[..notes.len()].copy_from_slice(notes);
+        sfx.speed = speed;
+        sfx.loop_start = loop_start;
+        sfx.loop_end = loop_end;
+
+        sound_effects
+    }
+
+    fn note(waveform: Waveform, volume: u8) -> Note {
+        Note {
+            pitch: 33,
+            waveform,
+            volume,
+            effect: Effect::None,
+        }
+    }
+
+    fn render(audio: &mut Audio, sound_effects: &SoundEffects, len: usize) -> Vec<f32> {
+        render_music(audio, sound_effects, &Music::new(), len)
+    }
+
+    fn render_music(
+        audio: &mut Audio,
+        sound_effects: &SoundEffects,
+        music: &Music,
+        len: usize,
+    ) -> Vec<f32> {
+        let mut output = MemoryOutput::new();
+        audio
+            .render_to(sound_effects, music, len, &mut output)
+            .unwrap();
+
+        output.samples().to_vec()
+    }
+
+    fn playing_sfxs(audio: &Audio) -> Vec<Option<usize>> {
+        (0..CHANNELS)
+            .map(|channel| audio.playing_sfx(channel))
+            .collect()
+    }
+
+    /// Sfx 3 lasts 2 notes (a loop start without a loop end sets its length),
+    /// sfx 4 lasts 32 notes. Both at speed 1.
+    fn music_sound_effects() -> SoundEffects {
+        let mut sound_effects = sound_effects(&[note(Waveform::Square, 7); 2], 1, 2, 0);
+        let long = sound_effects.get_mut(4).unwrap();
+        long.notes = [note(Waveform::Triangle, 7); 32];
+        long.speed = 1;
+
+        sound_effects
+    }
+
+    fn pattern(channels: [Option<u8>; CHANNELS]) -> Pattern {
+        Pattern {
+            channels,
+            ..Pattern::new()
+        }
+    }
+
+    #[test]
+    fn sfx_plays_its_notes_for_speed_ticks() {
+        let notes = [note(Waveform::Square, 7), note(Waveform::Square, 7)];
+        let sound_effects = sound_effects(&notes, 2, 0, 0);
+        let mut audio = Audio::new();
+        audio.sfx(3, -1, 0, 32);
+
+        let note_samples = 2 * TICK_SAMPLES;
+        render(&mut audio, &sound_effects, note_samples - 1);
+        assert_eq!(audio.playing_note(0), Some(0));
+
+        render(&mut audio, &sound_effects, 1);
+        assert_eq!(audio.playing_note(0), Some(1));
+        assert_eq!(audio.playing_sfx(0), Some(3));
+    }
+
+    #[test]
+    fn square_wave_at_full_volume() {
+        let sound_effects = sound_effects(&[note(Waveform::Square, 7)], 1, 0, 0);
+        let mut audio = Audio::new();
+        audio.sfx(3, 0, 0, 32);
+
+        let samples = render(&mut audio, &sound_effects, 50);
+
+        // 440Hz at 22050Hz: ~50 samples per period, half of them high.
+        assert!(samples[..25].iter().all(|&sample| sample == 0.25));
+        assert!(samples[26..50].iter().all(|&sample| sample == -0.25));
+    }
+
+    #[test]
+    fn volume_scales_samples() {
+        let sound_effects = sound_effects(&[note(Waveform::Square, 1)], 1, 0, 0);
+        let mut audio = Audio::new();
+        audio.sfx(3, 0, 0, 32);
+
+        let samples = render(&mut audio, &sound_effects, 10);
+
+        assert!(samples.iter().all(|&sample| sample == 0.25 / 7.0));
+    }
+
+    #[test]
+    fn channel_is_freed_when_sfx_ends() {
+        let sound_effects = sound_effects(&[note(Waveform::Saw, 5)], 1, 0, 0);
+        let mut audio = Audio::new();
+        audio.sfx(3, 2, 0, 32);
+
+        let samples = render(&mut audio, &sound_effects, 33 * TICK_SAMPLES);
+
+        assert_eq!(audio.playing_sfx(2), None);
+        assert!(samples[TICK_SAMPLES..].iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn loops_until_released() {
+        let notes = [note(Waveform::Triangle, 5); 4];
+        let sound_effects = sound_effects(&notes, 1, 1, 3);
+        let mut audio = Audio::new();
+        audio.sfx(3, 0, 0, 32);
+
+        render(&mut audio, &sound_effects, 3 * TICK_SAMPLES);
+        assert_eq!(audio.playing_note(0), Some(1));
+
+        render(&mut audio, &sound_effects, 100 * TICK_SAMPLES);
+        assert_eq!(audio.playing_sfx(0), Some(3));
+
+        audio.sfx(-2, 0, 0, 0);
+        render(&mut audio, &sound_effects, 32 * TICK_SAMPLES);
+        assert_eq!(audio.playing_sfx(0), None);
+    }
+
+    #[test]
+    fn offset_and_length_select_notes() {
+        let sound_effects = sound_effects(&[note(Waveform::Organ, 3); 32], 1, 0, 0);
+        let mut audio = Audio::new();
+        audio.sfx(3, 1, 4, 2);
+        assert_eq!(audio.playing_note(1), Some(4));
+
+        render(&mut audio, &sound_effects, 2 * TICK_SAMPLES - 1);
+        assert_eq!(audio.playing_note(1), Some(5));
+
+        render(&mut audio, &sound_effects, 2);
+        assert_eq!(audio.playing_sfx(1), None);
+    }
+
+    #[test]
+    fn sfx_picks_free_channels_and_can_be_stopped() {
+        let mut audio = Audio::new();
+        audio.sfx(3, -1, 0, 32);
+        audio.sfx(4, -1, 0, 32);
+        audio.sfx(5, 3, 0, 32);
+
+        assert_eq!(
+            (0..CHANNELS)
+                .map(|channel| audio.playing_sfx(channel))
+                .collect::<Vec<_>>(),
+            [Some(3), Some(4), None, Some(5)]
+        );
+
+        audio.sfx(4, -2, 0, 0);
+        assert_eq!(audio.playing_sfx(1), None);
+
+        audio.sfx(-1, 0, 0, 0);
+        assert_eq!(audio.playing_sfx(0), None);
+
+        audio.sfx(-1, -1, 0, 0);
+        assert_eq!(audio.playing_sfx(3), None);
+    }
+
+    #[test]
+    fn music_plays_patterns_for_their_leftmost_sfx_length() {
+        let sound_effects = music_sound_effects();
+        let mut music = Music::new();
+        *music.get_mut(0).unwrap() = pattern([None, Some(3), Some(4), None]);
+        *music.get_mut(1).unwrap() = pattern([Some(4), None, None, None]);
+        music.get_mut(1).unwrap().stop = true;
+        let mut audio = Audio::new();
+        audio.music(0, 0, 0);
+
+        render_music(&mut audio, &sound_effects, &music, 1);
+        assert_eq!(playing_sfxs(&audio), [None, Some(3), Some(4), None]);
+        assert_eq!(audio.playing_pattern(), Some(0));
+
+        // The first pattern lasts as long as sfx 3: 2 notes of 1 tick.
+        render_music(&mut audio, &sound_effects, &music, 2 * TICK_SAMPLES - 1);
+        assert_eq!(audio.playing_note(2), Some(2));
+        assert_eq!(audio.playing_pattern(), Some(0));
+
+        // The next one replaces every channel used by music.
+        render_music(&mut audio, &sound_effects, &music, 1);
+        assert_eq!(playing_sfxs(&audio), [Some(4), None, None, None]);
+        assert_eq!(audio.playing_pattern(), Some(1));
+        assert_eq!(audio.playing_note(0), Some(0));
+
+        render_music(&mut audio, &sound_effects, &music, 32 * TICK_SAMPLES);
+        assert_eq!(playing_sfxs(&audio), [None; CHANNELS]);
+        assert_eq!(audio.playing_pattern(), None);
+    }
+
+    #[test]
+    fn music_loops_back_to_loop_start() {
+        let sound_effects = music_sound_effects();
+        let mut music = Music::new();
+        *music.get_mut(0).unwrap() = pattern([Some(3), None, None, None]);
+        *music.get_mut(1).unwrap() = pattern([Some(3), None, None, None]);
+        music.get_mut(1).unwrap().loop_start = true;
+        music.get_mut(1).unwrap().loop_end = true;
+        let mut audio = Audio::new();
+        audio.music(0, 0, 0);
+
+        render_music(&mut audio, &sound_effects, &music, 2 * TICK_SAMPLES + 1);
+        assert_eq!(audio.playing_pattern(), Some(1));
+
+        render_music(&mut audio, &sound_effects, &music, 10 * TICK_SAMPLES);
+        assert_eq!(audio.playing_pattern(), Some(1));
+        assert_eq!(audio.playing_sfx(0), Some(3));
+
+        audio.music(-1, 0, 0);
+        assert_eq!(audio.playing_pattern(), None);
+        assert_eq!(audio.playing_sfx(0), None);
+    }
+
+    #[test]
+    fn music_fades_in_and_out() {
+        let sound_effects = music_sound_effects();
+        let mut music = Music::new();
+        *music.get_mut(0).unwrap() = pattern([Some(4), None, None, None]);
+        let mut audio = Audio::new();
+        audio.music(0, 100, 0);
+
+        let fade_samples = SAMPLE_RATE as usize / 10;
+        let samples = render_music(&mut audio, &sound_effects, &music, fade_samples);
+        let peak = |samples: &[f32]| samples.iter().fold(0.0_f32, |max, s| max.max(s.abs()));
+        assert!(peak(&samples[..100]) < 0.05);
+        assert!(peak(&samples[fade_samples - 100..]) > 0.45);
+
+        audio.music(-1, 100, 0);
+        assert_eq!(audio.playing_pattern(), Some(0));
+        render_music(&mut audio, &sound_effects, &music, fade_samples);
+        assert_eq!(audio.playing_pattern(), None);
+        assert_eq!(audio.playing_sfx(0), None);
+    }
+
+    #[test]
+    fn sfx_avoids_channels_reserved_for_music() {
+        let sound_effects = music_sound_effects();
+        let mut music = Music::new();
+        *music.get_mut(0).unwrap() = pattern([None, None, Some(4), None]);
+        let mut audio = Audio::new();
+        audio.music(0, 0, 0b0011);
+        render_music(&mut audio, &sound_effects, &music, 1);
+
+        audio.sfx(3, -1, 0, 32);
+        assert_eq!(playing_sfxs(&audio), [None, None, Some(4), Some(3)]);
+
+        // Explicitly choosing a channel takes it away from music.
+        audio.sfx(3, 2, 0, 32);
+        audio.music(-1, 0, 0);
+        assert_eq!(playing_sfxs(&audio), [None, None, Some(3), Some(3)]);
+    }
+}
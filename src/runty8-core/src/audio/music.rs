@@ -0,0 +1,311 @@
+use itertools::Itertools;
+
+use super::sfx::SoundEffects;
+use super::synth;
+use super::{CHANNELS, SAMPLE_RATE};
+use crate::serialize::Serialize;
+
+/// A pico8 game's music: patterns of up to 4 sound effects played at the same time.
+#[derive(Debug, Clone)]
+pub struct Music {
+    patterns: Vec<Pattern>,
+}
+
+impl Music {
+    pub fn file_name() -> String {
+        "music.txt".to_owned()
+    }
+}
+
+impl Music {
+    pub const PATTERN_COUNT: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            patterns: vec![Pattern::new(); Self::PATTERN_COUNT],
+        }
+    }
+
+    pub fn get(&self, pattern: usize) -> Option<&Pattern> {
+        self.patterns.get(pattern)
+    }
+
+    pub fn get_mut(&mut self, pattern: usize) -> Option<&mut Pattern> {
+        self.patterns.get_mut(pattern)
+    }
+
+    /// Parses the format used by the `__music__` section of `.p8` files:
+    /// one line per pattern.
+    pub fn deserialize(str: &str) -> Result<Self, String> {
+        let patterns = str
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Pattern::deserialize)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if patterns.len() != Self::PATTERN_COUNT {
+            return Err(format!(
+                "[Music] Needed {} patterns, got {}",
+                Self::PATTERN_COUNT,
+                patterns.len()
+            ));
+        }
+
+        Ok(Self { patterns })
+    }
+
+    /// Pattern that follows `pattern`, honoring its flags.
+    fn next_pattern(&self, pattern: usize) -> Option<usize> {
+        let current = self.get(pattern)?;
+
+        if current.stop {
+            None
+        } else if current.loop_end {
+            let loop_start = (0..=pattern)
+                .rev()
+                .find(|&pattern| self.patterns[pattern].loop_start);
+
+            Some(loop_start.unwrap_or(0))
+        } else {
+            Some(pattern + 1).filter(|&next| next < Self::PATTERN_COUNT)
+        }
+    }
+}
+
+impl Default for Music {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serialize for Music {
+    fn serialize(&self) -> String {
+        self.patterns.iter().map(Pattern::serialize).join("\n")
+    }
+}
+
+/// A music pattern: sound effects to play on each channel, and what to do once they end.
+///
+/// See: <https://pico-8.fandom.com/wiki/Memory#Music>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// Sound effect played on each channel, if any.
+    pub channels: [Option<u8>; CHANNELS],
+    /// Patterns with `loop_end` go back to the closest previous pattern with `loop_start`.
+    pub loop_start: bool,
+    pub loop_end: bool,
+    /// Stops the music when this pattern ends.
+    pub stop: bool,
+}
+
+impl Pattern {
+    const DISABLED_CHANNEL: u8 = 0x40;
+
+    pub fn new() -> Self {
+        Self {
+            channels: [None; CHANNELS],
+            loop_start: false,
+            loop_end: false,
+            stop: false,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.channels.iter().all(Option::is_none)
+    }
+
+    /// How long the pattern lasts, in samples.
+    ///
+    /// That's the length of the leftmost non-looping sound effect,
+    /// or of the leftmost one if all of them loop.
+    pub(crate) fn len(&self, sound_effects: &SoundEffects) -> usize {
+        let sfxs: Vec<_> = self
+            .channels
+            .iter()
+            .flatten()
+            .filter_map(|&sfx| sound_effects.get(sfx as usize))
+            .collect();
+
+        sfxs.iter()
+            .find(|sfx| !sfx.is_looping())
+            .or_else(|| sfxs.first())
+            .map(|sfx| sfx.end() * synth::note_samples(sfx))
+            .unwrap_or(0)
+    }
+
+    fn deserialize(line: &str) -> Result<Self, String> {
+        let (flags, channels) = line
+            .split_once(' ')
+            .ok_or_else(|| format!("[Music] Invalid pattern: {line}"))?;
+
+        let hex = |str: &str| u8::from_str_radix(str, 16).map_err(|err| format!("{err:?}"));
+        let flags = hex(flags)?;
+
+        if channels.len() != 2 * CHANNELS || !channels.is_ascii() {
+            return Err(format!("[Music] Invalid pattern channels: {channels}"));
+        }
+        let mut pattern_channels = [None; CHANNELS];
+        for (index, channel) in pattern_channels.iter_mut().enumerate() {
+            let byte = hex(&channels[2 * index..2 * index + 2])?;
+
+            *channel = (byte & Self::DISABLED_CHANNEL == 0).then_some(byte & 0x3f);
+        }
+
+        Ok(Self {
+            channels: pattern_channels,
+            loop_start: flags & 0b001 != 0,
+            loop_end: flags & 0b010 != 0,
+            stop: flags & 0b100 != 0,
+        })
+    }
+
+    fn serialize(&self) -> String {
+        let flags = self.loop_start as u8 | (self.loop_end as u8) << 1 | (self.stop as u8) << 2;
+        let channels: String = self
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(index, sfx)| {
+                let byte = sfx.unwrap_or(Self::DISABLED_CHANNEL | (index as u8 + 1));
+
+                format!("{byte:02x}")
+            })
+            .collect();
+
+        format!("{flags:02x} {channels}")
+    }
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The pattern being played, see [`Audio::music`](super::Audio).
+#[derive(Debug, Clone)]
+pub(crate) struct Sequencer {
+    pub(crate) pattern: usize,
+    started: bool,
+    /// Samples played of the current pattern.
+    pattern_sample: usize,
+    pattern_len: usize,
+    /// Channels that sound effects played through `sfx` shouldn't use.
+    pub(crate) channel_mask: u8,
+    volume: f32,
+    /// Volume change per sample, while fading.
+    fade_step: f32,
+}
+
+impl Sequencer {
+    pub(crate) fn new(pattern: usize, fade_millis: i32, channel_mask: u8) -> Self {
+        let (volume, fade_step) = if fade_millis > 0 {
+            (0.0, 1.0 / fade_samples(fade_millis))
+        } else {
+            (1.0, 0.0)
+        };
+
+        Self {
+            pattern,
+            started: false,
+            pattern_sample: 0,
+            pattern_len: 0,
+            channel_mask,
+            volume,
+            fade_step,
+        }
+    }
+
+    pub(crate) fn fade_out(&mut self, fade_millis: i32) {
+        self.fade_step = -1.0 / fade_samples(fade_millis);
+    }
+
+    pub(crate) fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Advances one sample. Returns what the channels should do.
+    pub(crate) fn step(&mut self, music: &Music, sound_effects: &SoundEffects) -> Step {
+        self.volume = (self.volume + self.fade_step).clamp(0.0, 1.0);
+        if self.fade_step < 0.0 && self.volume == 0.0 {
+            return Step::Stop;
+        }
+
+        let step = if !self.started {
+            self.started = true;
+            self.start(self.pattern, music, sound_effects)
+        } else if self.pattern_sample >= self.pattern_len {
+            match music.next_pattern(self.pattern) {
+                Some(next) => self.start(next, music, sound_effects),
+                None => Step::Stop,
+            }
+        } else {
+            Step::Continue
+        };
+        self.pattern_sample += 1;
+
+        step
+    }
+
+    fn start(&mut self, pattern: usize, music: &Music, sound_effects: &SoundEffects) -> Step {
+        match music.get(pattern).filter(|pattern| !pattern.is_empty()) {
+            Some(new_pattern) => {
+                self.pattern = pattern;
+                self.pattern_sample = 0;
+                self.pattern_len = new_pattern.len(sound_effects);
+
+                Step::Start(new_pattern.channels)
+            }
+            None => Step::Stop,
+        }
+    }
+}
+
+pub(crate) enum Step {
+    Continue,
+    /// A new pattern starts, with these sound effects.
+    Start([Option<u8>; CHANNELS]),
+    Stop,
+}
+
+fn fade_samples(fade_millis: i32) -> f32 {
+    (SAMPLE_RATE as f32 * fade_millis as f32 / 1000.0).max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_p8_music_lines() {
+        let pattern = Pattern::deserialize("05 0a0b4344").unwrap();
+
+        assert_eq!(
+            pattern,
+            Pattern {
+                channels: [Some(10), Some(11), None, None],
+                loop_start: true,
+                loop_end: false,
+                stop: true,
+            }
+        );
+        assert_eq!(pattern.serialize(), "05 0a0b4344");
+    }
+
+    #[test]
+    fn loop_end_goes_back_to_closest_loop_start() {
+        let mut music = Music::new();
+        music.get_mut(1).unwrap().loop_start = true;
+        music.get_mut(2).unwrap().loop_start = true;
+        music.get_mut(4).unwrap().loop_end = true;
+        music.get_mut(5).unwrap().stop = true;
+        music.get_mut(6).unwrap().loop_end = true;
+
+        assert_eq!(music.next_pattern(0), Some(1));
+        assert_eq!(music.next_pattern(4), Some(2));
+        assert_eq!(music.next_pattern(5), None);
+        assert_eq!(music.next_pattern(6), Some(2));
+        assert_eq!(music.next_pattern(63), None);
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+use std::io::{Seek, SeekFrom, Write};
+
+use super::SAMPLE_RATE;
+
+/// Destination for the samples produced by [`Audio`](super::Audio).
+pub trait AudioOutput: Debug {
+    /// Receives the next mono samples, in the range `-1.0..=1.0`, at [`SAMPLE_RATE`].
+    fn write(&mut self, samples: &[f32]) -> std::io::Result<()>;
+}
+
+/// Headless output that keeps every sample in memory.
+#[derive(Debug, Default)]
+pub struct MemoryOutput {
+    samples: Vec<f32>,
+}
+
+impl MemoryOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+}
+
+impl AudioOutput for MemoryOutput {
+    fn write(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        self.samples.extend_from_slice(samples);
+
+        Ok(())
+    }
+}
+
+/// Headless output that writes a 16-bit mono WAV file.
+///
+/// The header is kept up to date after every write,
+/// so the file is valid even if the game doesn't exit cleanly.
+#[derive(Debug)]
+pub struct WavOutput<W: Write + Seek> {
+    writer: W,
+    samples_written: u32,
+}
+
+impl WavOutput<std::fs::File> {
+    pub fn create(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Self::new(std::fs::File::create(path)?)
+    }
+}
+
+impl<W: Write + Seek> WavOutput<W> {
+    const HEADER_LEN: u32 = 44;
+    const BYTES_PER_SAMPLE: u32 = 2;
+
+    pub fn new(mut writer: W) -> std::io::Result<Self> {
+        writer.write_all(&Self::header(0))?;
+
+        Ok(Self {
+            writer,
+            samples_written: 0,
+        })
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// See: <http://soundfile.sapp.org/doc/WaveFormat/>
+    fn header(samples: u32) -> Vec<u8> {
+        let data_len = samples * Self::BYTES_PER_SAMPLE;
+        let byte_rate = SAMPLE_RATE * Self::BYTES_PER_SAMPLE;
+
+        [
+            b"RIFF".as_slice(),
+            &(Self::HEADER_LEN - 8 + data_len).to_le_bytes(),
+            b"WAVE",
+            b"fmt ",
+            &16_u32.to_le_bytes(),
+            // PCM
+            &1_u16.to_le_bytes(),
+            // Mono
+            &1_u16.to_le_bytes(),
+            &SAMPLE_RATE.to_le_bytes(),
+            &byte_rate.to_le_bytes(),
+            &(Self::BYTES_PER_SAMPLE as u16).to_le_bytes(),
+            &16_u16.to_le_bytes(),
+            b"data",
+            &data_len.to_le_bytes(),
+        ]
+        .concat()
+    }
+}
+
+impl<W: Write + Seek + Debug> AudioOutput for WavOutput<W> {
+    fn write(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        let pcm: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| ((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+            .collect();
+
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.write_all(&pcm)?;
+        self.samples_written += samples.len() as u32;
+
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&Self::header(self.samples_written))?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn wav_output_writes_valid_header_and_samples() {
+        let mut output = WavOutput::new(Cursor::new(vec![])).unwrap();
+        output.write(&[0.0, 1.0]).unwrap();
+        output.write(&[-1.0]).unwrap();
+
+        let bytes = output.into_inner().into_inner();
+        let u32_at = |index: usize| u32::from_le_bytes(bytes[index..index + 4].try_into().unwrap());
+        let i16_at = |index: usize| i16::from_le_bytes(bytes[index..index + 2].try_into().unwrap());
+
+        assert_eq!(bytes.len(), 44 + 3 * 2);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(4), 36 + 3 * 2);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(24), SAMPLE_RATE);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(40), 3 * 2);
+        assert_eq!(
+            [i16_at(44), i16_at(46), i16_at(48)],
+            [0, i16::MAX, -i16::MAX]
+        );
+    }
+}
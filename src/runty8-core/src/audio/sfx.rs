@@ -0,0 +1,315 @@
+use itertools::Itertools;
+
+use crate::serialize::Serialize;
+
+/// A pico8 game's sound effects.
+#[derive(Debug, Clone)]
+pub struct SoundEffects {
+    sfx: Vec<Sfx>,
+}
+
+impl SoundEffects {
+    pub fn file_name() -> String {
+        "sfx.txt".to_owned()
+    }
+}
+
+impl SoundEffects {
+    pub const SFX_COUNT: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            sfx: vec![Sfx::new(); Self::SFX_COUNT],
+        }
+    }
+
+    pub fn get(&self, sfx: usize) -> Option<&Sfx> {
+        self.sfx.get(sfx)
+    }
+
+    pub fn get_mut(&mut self, sfx: usize) -> Option<&mut Sfx> {
+        self.sfx.get_mut(sfx)
+    }
+
+    /// Parses the format used by the `__sfx__` section of `.p8` files:
+    /// one line per sound effect.
+    pub fn deserialize(str: &str) -> Result<Self, String> {
+        let sfx = str
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Sfx::deserialize)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if sfx.len() != Self::SFX_COUNT {
+            return Err(format!(
+                "[SoundEffects] Needed {} sound effects, got {}",
+                Self::SFX_COUNT,
+                sfx.len()
+            ));
+        }
+
+        Ok(Self { sfx })
+    }
+}
+
+impl Default for SoundEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serialize for SoundEffects {
+    fn serialize(&self) -> String {
+        self.sfx.iter().map(Sfx::serialize).join("\n")
+    }
+}
+
+/// A single sound effect: 32 notes played one after the other.
+///
+/// See: <https://pico-8.fandom.com/wiki/Memory#Sound_effects>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sfx {
+    pub notes: [Note; Sfx::NOTE_COUNT],
+    /// How long each note lasts, in ticks of 183 samples (roughly 1/120 of a second).
+    pub speed: u8,
+    /// If `loop_start < loop_end`, the notes in `loop_start..loop_end` are repeated
+    /// until the sound effect is stopped or released.
+    ///
+    /// If `loop_end` is 0, the sound effect ends before note `loop_start` instead.
+    pub loop_start: u8,
+    pub loop_end: u8,
+}
+
+impl Sfx {
+    pub const NOTE_COUNT: usize = 32;
+
+    pub fn new() -> Self {
+        Self {
+            notes: [Note::SILENT; Self::NOTE_COUNT],
+            speed: 16,
+            loop_start: 0,
+            loop_end: 0,
+        }
+    }
+
+    pub(crate) fn is_looping(&self) -> bool {
+        self.loop_start < self.loop_end
+    }
+
+    /// Index one past the last note to play, when not looping.
+    pub(crate) fn end(&self) -> usize {
+        if self.loop_end == 0 && self.loop_start > 0 {
+            (self.loop_start as usize).min(Self::NOTE_COUNT)
+        } else {
+            Self::NOTE_COUNT
+        }
+    }
+
+    fn deserialize(line: &str) -> Result<Self, String> {
+        const HEADER_LEN: usize = 8;
+        const NOTE_LEN: usize = 5;
+
+        let expected_len = HEADER_LEN + NOTE_LEN * Self::NOTE_COUNT;
+        if line.len() != expected_len || !line.is_ascii() {
+            return Err(format!(
+                "[SoundEffects] Needed {} hex digits per sound effect, got {}",
+                expected_len,
+                line.len()
+            ));
+        }
+
+        let hex = |start: usize, len: usize| {
+            u8::from_str_radix(&line[start..start + len], 16).map_err(|err| format!("{err:?}"))
+        };
+
+        // The first byte is only used by pico8's editor.
+        let speed = hex(2, 2)?;
+        let loop_start = hex(4, 2)?;
+        let loop_end = hex(6, 2)?;
+
+        let mut notes = [Note::SILENT; Self::NOTE_COUNT];
+        for (index, note) in notes.iter_mut().enumerate() {
+            let start = HEADER_LEN + index * NOTE_LEN;
+
+            *note = Note {
+                pitch: hex(start, 2)?,
+                waveform: Waveform::from_index(hex(start + 2, 1)?),
+                volume: hex(start + 3, 1)?.min(7),
+                effect: Effect::from_index(hex(start + 4, 1)?),
+            };
+        }
+
+        Ok(Self {
+            notes,
+            speed,
+            loop_start,
+            loop_end,
+        })
+    }
+
+    fn serialize(&self) -> String {
+        let header = format!(
+            "00{:02x}{:02x}{:02x}",
+            self.speed, self.loop_start, self.loop_end
+        );
+        let notes = self.notes.iter().map(|note| {
+            format!(
+                "{:02x}{:x}{:x}{:x}",
+                note.pitch, note.waveform as u8, note.volume, note.effect as u8
+            )
+        });
+
+        std::iter::once(header).chain(notes).collect()
+    }
+}
+
+impl Default for Sfx {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A note in a sound effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// Semitones above C2 (65.41Hz), in the range `0..=63`.
+    pub pitch: u8,
+    pub waveform: Waveform,
+    /// In the range `0..=7`. Notes with volume 0 are silent.
+    pub volume: u8,
+    pub effect: Effect,
+}
+
+impl Note {
+    pub const SILENT: Self = Self {
+        pitch: 0,
+        waveform: Waveform::Triangle,
+        volume: 0,
+        effect: Effect::None,
+    };
+}
+
+/// Pico8's instruments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Triangle = 0,
+    TiltedSaw = 1,
+    Saw = 2,
+    Square = 3,
+    Pulse = 4,
+    Organ = 5,
+    Noise = 6,
+    Phaser = 7,
+}
+
+impl Waveform {
+    // Custom instruments (the 4th bit) aren't supported,
+    // notes using them play their base waveform instead.
+    fn from_index(index: u8) -> Self {
+        match index & 0b111 {
+            0 => Self::Triangle,
+            1 => Self::TiltedSaw,
+            2 => Self::Saw,
+            3 => Self::Square,
+            4 => Self::Pulse,
+            5 => Self::Organ,
+            6 => Self::Noise,
+            _ => Self::Phaser,
+        }
+    }
+}
+
+/// Pico8's note effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    None = 0,
+    /// Slides pitch and volume from the previous note.
+    Slide = 1,
+    Vibrato = 2,
+    /// Drops the pitch down to 0.
+    Drop = 3,
+    FadeIn = 4,
+    FadeOut = 5,
+    /// Cycles through the pitches of the group of 4 notes this note belongs to.
+    ArpeggioFast = 6,
+    /// Like [`Effect::ArpeggioFast`], at half the speed.
+    ArpeggioSlow = 7,
+}
+
+impl Effect {
+    fn from_index(index: u8) -> Self {
+        match index & 0b111 {
+            0 => Self::None,
+            1 => Self::Slide,
+            2 => Self::Vibrato,
+            3 => Self::Drop,
+            4 => Self::FadeIn,
+            5 => Self::FadeOut,
+            6 => Self::ArpeggioFast,
+            _ => Self::ArpeggioSlow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> String {
+        format!("000c0410180501c0601e1621f373{}", "0".repeat(28 * 5))
+    }
+
+    #[test]
+    fn deserializes_p8_sfx_lines() {
+        let sfx = Sfx::deserialize(&line()).unwrap();
+
+        assert_eq!(sfx.speed, 12);
+        assert_eq!(sfx.loop_start, 4);
+        assert_eq!(sfx.loop_end, 16);
+        assert_eq!(
+            sfx.notes[..4],
+            [
+                Note {
+                    pitch: 0x18,
+                    waveform: Waveform::Triangle,
+                    volume: 5,
+                    effect: Effect::None,
+                },
+                Note {
+                    pitch: 0x1c,
+                    waveform: Waveform::Triangle,
+                    volume: 6,
+                    effect: Effect::None,
+                },
+                Note {
+                    pitch: 0x1e,
+                    waveform: Waveform::TiltedSaw,
+                    volume: 6,
+                    effect: Effect::Vibrato,
+                },
+                Note {
+                    pitch: 0x1f,
+                    waveform: Waveform::Square,
+                    volume: 7,
+                    effect: Effect::Drop,
+                },
+            ]
+        );
+        assert!(sfx.notes[4..].iter().all(|note| *note == Note::SILENT));
+    }
+
+    #[test]
+    fn serialize_round_trips() {
+        let sfx = Sfx::deserialize(&line()).unwrap();
+
+        assert_eq!(sfx.serialize(), line());
+    }
+
+    #[test]
+    fn rejects_wrong_number_of_sound_effects() {
+        let lines = std::iter::repeat_n(line(), 3).join("\n");
+
+        assert!(SoundEffects::deserialize(&lines).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Turns notes into samples.
+//!
+//! Waveforms and effects are approximations of pico8's, based on zepto8's
+//! reverse engineering: <https://github.com/samhocevar/zepto8/blob/master/src/synth.cpp>
+
+use super::sfx::{Effect, Note, Sfx, SoundEffects, Waveform};
+use super::SAMPLE_RATE;
+
+/// Samples in one tick. A note lasts `speed` ticks.
+pub(crate) const TICK_SAMPLES: usize = 183;
+
+/// A sound effect being played on a channel.
+#[derive(Debug, Clone)]
+pub(crate) struct Voice {
+    pub(crate) sfx: usize,
+    /// Index of the note being played.
+    pub(crate) note: usize,
+    /// How many samples of the current note have been played.
+    note_sample: usize,
+    /// Index one past the last note to play (loops can keep it from being reached).
+    end: usize,
+    /// Whether the sound effect's loop is still honored. See [`Voice::release`].
+    looping: bool,
+    /// Samples played since the sound effect started.
+    pub(crate) elapsed: usize,
+    /// Pitch and volume of the previous note, used by [`Effect::Slide`].
+    previous: (f32, f32),
+    oscillator: Oscillator,
+}
+
+impl Voice {
+    pub(crate) fn new(sfx: usize, offset: usize, length: usize) -> Self {
+        Self {
+            sfx,
+            note: offset,
+            note_sample: 0,
+            end: offset + length,
+            looping: true,
+            elapsed: 0,
+            previous: (0.0, 0.0),
+            oscillator: Oscillator::new(),
+        }
+    }
+
+    /// Stops looping, the sound effect plays until its end.
+    pub(crate) fn release(&mut self) {
+        self.looping = false;
+    }
+
+    /// Produces the next sample, or `None` if the sound effect has finished.
+    pub(crate) fn next_sample(&mut self, sound_effects: &SoundEffects) -> Option<f32> {
+        let sfx = sound_effects.get(self.sfx)?;
+        if self.note >= sfx.end().min(self.end) {
+            return None;
+        }
+
+        let note = sfx.notes[self.note];
+        let note_samples = note_samples(sfx);
+        let progress = self.note_sample as f32 / note_samples as f32;
+
+        let (pitch, volume) = self.apply_effect(sfx, note, progress);
+        let frequency = pitch_to_frequency(pitch);
+        let frequency = if note.effect == Effect::Drop {
+            frequency * (1.0 - progress)
+        } else {
+            frequency
+        };
+
+        let sample = self.oscillator.next(note.waveform, frequency) * volume / 7.0;
+
+        self.elapsed += 1;
+        self.note_sample += 1;
+        if self.note_sample >= note_samples {
+            self.next_note(sfx, note);
+        }
+
+        Some(sample)
+    }
+
+    fn apply_effect(&self, sfx: &Sfx, note: Note, progress: f32) -> (f32, f32) {
+        let pitch = note.pitch as f32;
+        let volume = note.volume as f32;
+
+        match note.effect {
+            Effect::None | Effect::Drop => (pitch, volume),
+            Effect::Slide => {
+                let (previous_pitch, previous_volume) = self.previous;
+
+                (
+                    lerp(previous_pitch, pitch, progress),
+                    lerp(previous_volume, volume, progress),
+                )
+            }
+            Effect::Vibrato => {
+                let seconds = self.elapsed as f32 / SAMPLE_RATE as f32;
+                let offset = ((7.5 * seconds).fract() - 0.5).abs() - 0.25;
+
+                (pitch + offset, volume)
+            }
+            Effect::FadeIn => (pitch, volume * progress),
+            Effect::FadeOut => (pitch, volume * (1.0 - progress)),
+            Effect::ArpeggioFast | Effect::ArpeggioSlow => {
+                let mut step_ticks = if note.effect == Effect::ArpeggioFast {
+                    4
+                } else {
+                    8
+                };
+                if sfx.speed <= 8 {
+                    step_ticks /= 2;
+                }
+
+                let step = self.elapsed / TICK_SAMPLES / step_ticks;
+                let group_start = self.note & !0b11;
+                let arpeggio_note = sfx.notes[group_start + step % 4];
+
+                (arpeggio_note.pitch as f32, volume)
+            }
+        }
+    }
+
+    fn next_note(&mut self, sfx: &Sfx, finished_note: Note) {
+        self.previous = (finished_note.pitch as f32, finished_note.volume as f32);
+        self.note_sample = 0;
+        self.note += 1;
+
+        if self.looping && sfx.is_looping() && self.note >= sfx.loop_end as usize {
+            self.note = sfx.loop_start as usize;
+        }
+    }
+}
+
+pub(crate) fn note_samples(sfx: &Sfx) -> usize {
+    sfx.speed.max(1) as usize * TICK_SAMPLES
+}
+
+/// Pitch 33 is A4 (440Hz).
+fn pitch_to_frequency(pitch: f32) -> f32 {
+    440.0 * 2.0_f32.powf((pitch - 33.0) / 12.0)
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+#[derive(Debug, Clone)]
+struct Oscillator {
+    /// Position in the current period, in `0.0..1.0`.
+    phase: f32,
+    /// Slower phase (1/128 of the frequency), used by [`Waveform::Phaser`].
+    sub_phase: f32,
+    noise_state: u32,
+    noise_sample: f32,
+}
+
+impl Oscillator {
+    fn new() -> Self {
+        Self {
+            phase: 0.0,
+            sub_phase: 0.0,
+            noise_state: 0x2545_f491,
+            noise_sample: 0.0,
+        }
+    }
+
+    fn next(&mut self, waveform: Waveform, frequency: f32) -> f32 {
+        let t = self.phase;
+
+        let sample = match waveform {
+            Waveform::Triangle => 0.5 * ((4.0 * t - 2.0).abs() - 1.0),
+            Waveform::TiltedSaw => {
+                const PEAK: f32 = 0.9;
+
+                let sample = if t < PEAK {
+                    2.0 * t / PEAK - 1.0
+                } else {
+                    2.0 * (1.0 - t) / (1.0 - PEAK) - 1.0
+                };
+
+                0.5 * sample
+            }
+            Waveform::Saw => 0.653 * if t < 0.5 { t } else { t - 1.0 },
+            Waveform::Square => square(t, 0.5),
+            Waveform::Pulse => square(t, 1.0 / 3.0),
+            Waveform::Organ => {
+                let sample = if t < 0.5 {
+                    3.0 - (24.0 * t - 6.0).abs()
+                } else {
+                    1.0 - (16.0 * t - 12.0).abs()
+                };
+
+                sample / 9.0
+            }
+            Waveform::Noise => self.noise_sample,
+            Waveform::Phaser => {
+                let k = (2.0 * self.sub_phase - 1.0).abs();
+                let u = (t + 0.5 * k).fract();
+
+                ((4.0 * u - 2.0).abs() - (8.0 * t - 4.0).abs()) / 6.0
+            }
+        };
+
+        let step = frequency / SAMPLE_RATE as f32;
+        let half_periods_before = (2.0 * self.phase) as u32;
+        self.phase += step;
+        self.sub_phase = (self.sub_phase + step / 128.0).fract();
+
+        // Noise is a random value held for half a period,
+        // so the note's pitch still affects how it sounds.
+        if (2.0 * self.phase) as u32 != half_periods_before {
+            self.noise_sample = 0.25 * self.next_random();
+        }
+        self.phase = self.phase.fract();
+
+        sample
+    }
+
+    /// Xorshift, returns a value in `-1.0..=1.0`.
+    fn next_random(&mut self) -> f32 {
+        let mut x = self.noise_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.noise_state = x;
+
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+fn square(t: f32, duty_cycle: f32) -> f32 {
+    if t < duty_cycle {
+        0.25
+    } else {
+        -0.25
+    }
+}
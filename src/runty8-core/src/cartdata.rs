@@ -0,0 +1,269 @@
+use itertools::Itertools;
+
+use crate::serialize::{self, Serialize};
+use crate::Fixed;
+
+/// Persistent data of a cart, see <https://pico-8.fandom.com/wiki/Cartdata>.
+///
+/// Saved in `<directory>/<id>/cartdata.txt`: a file on native, a `localStorage` key on wasm.
+#[derive(Clone, Debug)]
+pub(crate) struct CartData {
+    /// Where the values are saved, `None` until `cartdata` is called.
+    file_path: Option<String>,
+    values: [Fixed; CartData::SLOTS],
+}
+
+impl CartData {
+    pub(crate) const SLOTS: usize = 64;
+    const VALUES_PER_LINE: usize = 8;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            file_path: None,
+            values: [Fixed::ZERO; Self::SLOTS],
+        }
+    }
+
+    pub(crate) fn file_name() -> String {
+        "cartdata.txt".to_owned()
+    }
+
+    /// Starts saving to the data of cart `id` (in `directory`), loading it if it exists.
+    /// Returns whether there was data to load.
+    pub(crate) fn open(&mut self, directory: &str, id: &str) -> Result<bool, String> {
+        if self.file_path.is_some() {
+            return Err("cartdata can only be called once".to_owned());
+        }
+        validate_id(id)?;
+
+        let file_path = format!("{directory}/{id}/{}", Self::file_name());
+        let loaded = match crate::read(&file_path) {
+            Some(contents) => {
+                self.values = Self::deserialize(&contents)?.values;
+                true
+            }
+            None => false,
+        };
+        self.file_path = Some(file_path);
+
+        Ok(loaded)
+    }
+
+    /// Value in slot `index`, 0 if the slot doesn't exist.
+    pub(crate) fn dget(&self, index: i32) -> Fixed {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.values.get(index))
+            .copied()
+            .unwrap_or(Fixed::ZERO)
+    }
+
+    /// Sets slot `index` and saves the data if it changed and `open` was called.
+    /// Slots that don't exist are ignored.
+    pub(crate) fn dset(&mut self, index: i32, value: Fixed) -> Result<(), String> {
+        let slot = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.values.get_mut(index));
+
+        match slot {
+            Some(slot) if *slot != value => {
+                *slot = value;
+                self.save()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(file_path) = &self.file_path else {
+            return Ok(());
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(directory) = std::path::Path::new(file_path).parent() {
+            std::fs::create_dir_all(directory).map_err(|err| format!("{err:?}"))?;
+        }
+
+        serialize::write(file_path, &self.serialize()).map_err(|err| format!("{err:?}"))
+    }
+
+    pub(crate) fn deserialize(file_contents: &str) -> Result<Self, String> {
+        let values = file_contents
+            .lines()
+            .flat_map(|line| line.as_bytes().chunks(8))
+            .map(|hex| {
+                let hex = std::str::from_utf8(hex).map_err(|err| format!("{err:?}"))?;
+
+                u32::from_str_radix(hex, 16)
+                    .map(|bits| Fixed::from_bits(bits as i32))
+                    .map_err(|err| format!("{err:?}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let values = values.try_into().map_err(|v: Vec<Fixed>| {
+            format!(
+                "Incorrect number of elements, needed: {}, got: {}",
+                Self::SLOTS,
+                v.len()
+            )
+        })?;
+
+        Ok(Self {
+            file_path: None,
+            values,
+        })
+    }
+}
+
+impl Serialize for CartData {
+    /// Like pico8's `.p8d.txt` files: 8 lines of 8 values, each one as 8 hex digits.
+    fn serialize(&self) -> String {
+        self.values
+            .chunks(Self::VALUES_PER_LINE)
+            .map(|line| {
+                line.iter()
+                    .map(|value| format!("{:08x}", value.to_bits()))
+                    .join("")
+            })
+            .join("\n")
+    }
+}
+
+/// Ids can have up to 64 characters: lowercase letters, digits and underscores.
+fn validate_id(id: &str) -> Result<(), String> {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+
+    if !id.is_empty() && id.len() <= 64 && id.chars().all(valid_char) {
+        Ok(())
+    } else {
+        Err(format!("Invalid cartdata id: {id:?}"))
+    }
+}
+
+/// Directory where every cart's data is saved:
+///  - Native: `runty8/cartdata` in the user's data directory.
+///  - Web: `cartdata`, used as a prefix for the `localStorage` keys.
+pub(crate) fn directory() -> String {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let data_directory = std::env::var("XDG_DATA_HOME")
+            .or_else(|_| std::env::var("HOME").map(|home| format!("{home}/.local/share")))
+            .or_else(|_| std::env::var("APPDATA"))
+            .unwrap_or_else(|_| ".".to_owned());
+
+        format!("{data_directory}/runty8/cartdata")
+    }
+    #[cfg(target_arch = "wasm32")]
+    return "cartdata".to_owned();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CartData;
+    use crate::Fixed;
+
+    /// Fresh directory for a test, removed when dropped.
+    struct TempDir(std::path::PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("runty8-cartdata-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+
+            Self(path)
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn values_survive_restarts() {
+        let dir = TempDir::new("restarts");
+
+        let mut cart_data = CartData::new();
+        assert_eq!(cart_data.open(dir.path(), "my_game"), Ok(false));
+        cart_data.dset(0, Fixed::from(42)).unwrap();
+        cart_data.dset(63, Fixed::from(-1.5)).unwrap();
+
+        let mut cart_data = CartData::new();
+        assert_eq!(cart_data.open(dir.path(), "my_game"), Ok(true));
+        assert_eq!(cart_data.dget(0), Fixed::from(42));
+        assert_eq!(cart_data.dget(63), Fixed::from(-1.5));
+        assert_eq!(cart_data.dget(1), Fixed::ZERO);
+    }
+
+    #[test]
+    fn each_id_has_its_own_data() {
+        let dir = TempDir::new("ids");
+
+        let mut first = CartData::new();
+        first.open(dir.path(), "first").unwrap();
+        first.dset(5, Fixed::from(5)).unwrap();
+
+        let mut second = CartData::new();
+        assert_eq!(second.open(dir.path(), "second"), Ok(false));
+        assert_eq!(second.dget(5), Fixed::ZERO);
+        assert!(dir.0.join("first").join(CartData::file_name()).exists());
+    }
+
+    #[test]
+    fn only_changes_are_saved() {
+        let dir = TempDir::new("changes");
+        let file_path = dir.0.join("my_game").join(CartData::file_name());
+
+        let mut cart_data = CartData::new();
+        cart_data.open(dir.path(), "my_game").unwrap();
+        cart_data.dset(0, Fixed::ZERO).unwrap();
+        assert!(!file_path.exists());
+
+        cart_data.dset(0, Fixed::from(3)).unwrap();
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn missing_slots_are_ignored() {
+        let mut cart_data = CartData::new();
+        cart_data.dset(64, Fixed::from(1)).unwrap();
+        cart_data.dset(-1, Fixed::from(1)).unwrap();
+
+        assert_eq!(cart_data.dget(64), Fixed::ZERO);
+        assert_eq!(cart_data.dget(-1), Fixed::ZERO);
+    }
+
+    #[test]
+    fn cartdata_can_only_be_opened_once_with_a_valid_id() {
+        let dir = TempDir::new("once");
+        let mut cart_data = CartData::new();
+
+        assert!(cart_data.open(dir.path(), "Not Valid").is_err());
+        assert!(cart_data.open(dir.path(), "").is_err());
+        assert!(cart_data.open(dir.path(), "valid_123").is_ok());
+        assert!(cart_data.open(dir.path(), "valid_123").is_err());
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let mut cart_data = CartData::new();
+        cart_data.dset(9, Fixed::from_bits(0x1234_5678)).unwrap();
+        let serialized = crate::serialize::Serialize::serialize(&cart_data);
+
+        assert_eq!(serialized.lines().count(), 8);
+        assert_eq!(
+            serialized.lines().nth(1).unwrap(),
+            "0000000012345678000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(
+            CartData::deserialize(&serialized).unwrap().dget(9),
+            Fixed::from_bits(0x1234_5678)
+        );
+    }
+}
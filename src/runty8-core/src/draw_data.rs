@@ -1,38 +1,69 @@
 use crate::flags::Flags;
-use crate::map::Map;
+use crate::map::{Map, MapOptions};
 use crate::sprite_sheet::SpriteSheet;
 use crate::util::{min_max, MinMax};
-use crate::Color;
 use crate::{draw, font};
+use crate::{Color, Fixed};
 
 use crate::sprite_sheet::Sprite;
 
 const WIDTH: usize = 128;
-const NUM_COMPONENTS: usize = 3;
+const PIXELS_PER_BYTE: usize = 2;
 
-/// A raw buffer made up of `RGB` components: [R, G, B, R, G, B, ...].
-type Buffer = [u8; NUM_COMPONENTS * WIDTH * WIDTH];
-const BLACK_BUFFER: Buffer = [0; NUM_COMPONENTS * WIDTH * WIDTH];
+/// The screen, as pico8 colors packed 2 pixels per byte (left pixel in the low 4 bits),
+/// like pico8's screen memory.
+type Buffer = [u8; WIDTH * WIDTH / PIXELS_PER_BYTE];
+const BLACK_BUFFER: Buffer = [0; WIDTH * WIDTH / PIXELS_PER_BYTE];
 
-const ORIGINAL_PALETTE: [Color; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+const DEFAULT_PEN_COLOR: Color = 6;
+
+pub(crate) const ORIGINAL_PALETTE: [Color; 16] =
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+/// Only black is transparent.
+const DEFAULT_TRANSPARENCY: u16 = 0x8000;
 
 // Handles actually drawing to the screen buffer
 #[derive(Debug)]
 pub struct DrawData {
     buffer: Buffer,
     // Maybe these properties below should be in internal state?
-    transparent_color: Option<Color>,
+    /// Bit `15 - c` is set if color `c` is transparent, like pico8's `palt(mask)`.
+    transparency: u16,
     draw_palette: [Color; 16],
+    /// Applied when presenting the screen, it doesn't affect drawn pixels.
+    screen_palette: [Color; 16],
+    /// Screen areas the screen palette applies to, the whole screen if there are none.
+    screen_palette_areas: Vec<ClipRect>,
     camera: (i32, i32),
+    clip: ClipRect,
+    fill_pattern: FillPattern,
+    /// Where `print` writes when not given coordinates.
+    cursor: (i32, i32),
+    pen_color: Color,
+    /// Where [`DrawData::line_to`] continues from, if `line_end_valid`.
+    /// Kept while invalid, like pico8's `0x5f3c..0x5f40`.
+    line_end: (i32, i32),
+    /// `false` if [`DrawData::line_to`] should only move the end.
+    line_end_valid: bool,
+    tline_wrap: TlineWrap,
 }
 
 impl DrawData {
     pub fn new() -> Self {
         DrawData {
             buffer: BLACK_BUFFER,
-            transparent_color: Some(0),
+            transparency: DEFAULT_TRANSPARENCY,
             draw_palette: ORIGINAL_PALETTE,
+            screen_palette: ORIGINAL_PALETTE,
+            screen_palette_areas: Vec::new(),
             camera: (0, 0),
+            clip: ClipRect::FULL_SCREEN,
+            fill_pattern: FillPattern::SOLID,
+            cursor: (0, 0),
+            pen_color: DEFAULT_PEN_COLOR,
+            line_end: (0, 0),
+            line_end_valid: false,
+            tline_wrap: TlineWrap::default(),
         }
     }
 
@@ -41,58 +72,150 @@ impl DrawData {
     }
 
     fn set_pixel_with_transparency(&mut self, index: usize, color: Color) {
-        if let Some(transparent_color) = self.transparent_color {
-            if color == transparent_color {
-                return;
-            }
+        if !self.is_transparent(color) {
+            self.set_pixel(index, color);
         }
+    }
 
-        self.set_pixel(index, color);
+    fn is_transparent(&self, color: Color) -> bool {
+        self.transparency & transparency_bit(color) != 0
     }
 
     fn set_pixel(&mut self, index: usize, color: Color) {
         // https://pico-8.fandom.com/wiki/Pal
-        let color = self.draw_palette[color as usize];
-        let c = get_color(color);
+        let color = self.draw_palette[(color & 0x0f) as usize];
+        let (byte, shift) = pixel_location(index);
 
-        #[allow(clippy::identity_op)]
-        {
-            let r = ((c >> 16) & 0x0000FF) as u8;
-            let g = ((c >> 8) & 0x0000FF) as u8;
-            let b = ((c >> 0) & 0x0000FF) as u8;
-            self.buffer[NUM_COMPONENTS * index + 0] = r;
-            self.buffer[NUM_COMPONENTS * index + 1] = g;
-            self.buffer[NUM_COMPONENTS * index + 2] = b;
-        }
+        self.buffer[byte] = self.buffer[byte] & !(0x0f << shift) | color << shift;
     }
 
-    /// Get the "buffer color" (as opposed to a Pico8 color) currently in the
-    /// screen at the given index.
-    fn get_pixel(&self, index: usize) -> u32 {
-        // TODO: Should the current palette affect this?
-        #[allow(clippy::identity_op)]
-        {
-            let r = self.buffer[NUM_COMPONENTS * index + 0] as u32;
-            let g = self.buffer[NUM_COMPONENTS * index + 1] as u32;
-            let b = self.buffer[NUM_COMPONENTS * index + 2] as u32;
+    /// Get the pico8 color currently in the screen at the given index.
+    fn get_pixel(&self, index: usize) -> Color {
+        let (byte, shift) = pixel_location(index);
 
-            r << 16 | g << 8 | b << 0
-        }
+        (self.buffer[byte] >> shift) & 0x0f
     }
 
+    /// The screen, see [`Buffer`]. Colors have gone through the draw palette,
+    /// but not through the screen palette, which is applied when presenting it.
     pub fn buffer(&self) -> &Buffer {
         &self.buffer
     }
 
-    fn print_char(&mut self, index: usize, x: i32, y: i32, color: Color) {
-        let char_data = font::FONT.get(index).unwrap_or(&font::MISSING_CHAR);
+    /// Color each drawn color is displayed as. It can be one of the extended colors.
+    pub fn screen_palette(&self) -> &[Color; 16] {
+        &self.screen_palette
+    }
+
+    /// Screen palette the pixel at `(x, y)` is displayed with: the original palette
+    /// if it's outside the areas set with [`DrawData::screen_palette_areas`].
+    pub fn screen_palette_at(&self, x: i32, y: i32) -> &[Color; 16] {
+        if self.screen_palette_areas.is_empty()
+            || self
+                .screen_palette_areas
+                .iter()
+                .any(|area| area.contains(x, y))
+        {
+            &self.screen_palette
+        } else {
+            &ORIGINAL_PALETTE
+        }
+    }
+
+    pub(crate) fn buffer_mut(&mut self) -> &mut Buffer {
+        &mut self.buffer
+    }
+
+    /// Byte `offset` of the draw state registers (pico8's `0x5f00..0x5f40`),
+    /// or `None` if that byte isn't backed by any of the draw state.
+    /// See <https://pico-8.fandom.com/wiki/Memory#Draw_state>.
+    pub(crate) fn peek_register(&self, offset: usize) -> Option<u8> {
+        let camera = [self.camera.0 as i16, self.camera.1 as i16];
+        let line_end = [self.line_end.0 as i16, self.line_end.1 as i16];
+
+        let value = match offset {
+            0x00..=0x0f => {
+                let color = offset as Color;
+                let transparent = if self.is_transparent(color) { 0x10 } else { 0 };
+
+                self.draw_palette[offset] | transparent
+            }
+            0x10..=0x1f => self.screen_palette[offset - 0x10],
+            0x20 => self.clip.x0 as u8,
+            0x21 => self.clip.y0 as u8,
+            0x22 => self.clip.x1 as u8,
+            0x23 => self.clip.y1 as u8,
+            0x25 => self.pen_color,
+            0x26 => self.cursor.0 as u8,
+            0x27 => self.cursor.1 as u8,
+            0x28..=0x2b => camera[(offset - 0x28) / 2].to_le_bytes()[offset % 2],
+            0x31..=0x32 => self.fill_pattern.pattern.to_le_bytes()[offset - 0x31],
+            0x33 => self.fill_pattern.transparent as u8,
+            0x35 => !self.line_end_valid as u8,
+            0x38 => self.tline_wrap.size.0,
+            0x39 => self.tline_wrap.size.1,
+            0x3a => self.tline_wrap.offset.0,
+            0x3b => self.tline_wrap.offset.1,
+            0x3c..=0x3f => line_end[(offset - 0x3c) / 2].to_le_bytes()[offset % 2],
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Writes byte `offset` of the draw state registers, see [`DrawData::peek_register`].
+    /// Bytes that aren't backed by any of the draw state are ignored.
+    pub(crate) fn poke_register(&mut self, offset: usize, value: u8) {
+        let set_byte = |n: i32, byte: usize| {
+            let mut bytes = (n as i16).to_le_bytes();
+            bytes[byte] = value;
+
+            i16::from_le_bytes(bytes) as i32
+        };
+        let clamp = |value: u8| (value as i32).min(WIDTH as i32);
+
+        match offset {
+            0x00..=0x0f => {
+                let color = offset as Color;
+
+                self.draw_palette[offset] = value & 0x0f;
+                self.palt_(color, value & 0x10 != 0);
+            }
+            0x10..=0x1f => self.screen_palette[offset - 0x10] = value & 0x8f,
+            0x20 => self.clip.x0 = clamp(value),
+            0x21 => self.clip.y0 = clamp(value),
+            0x22 => self.clip.x1 = clamp(value),
+            0x23 => self.clip.y1 = clamp(value),
+            0x25 => self.pen_color = value,
+            0x26 => self.cursor.0 = value as i32,
+            0x27 => self.cursor.1 = value as i32,
+            0x28..=0x29 => self.camera.0 = set_byte(self.camera.0, offset - 0x28),
+            0x2a..=0x2b => self.camera.1 = set_byte(self.camera.1, offset - 0x2a),
+            0x31..=0x32 => {
+                let mut bytes = self.fill_pattern.pattern.to_le_bytes();
+                bytes[offset - 0x31] = value;
+
+                self.fill_pattern.pattern = u16::from_le_bytes(bytes);
+            }
+            0x33 => self.fill_pattern.transparent = value & 1 != 0,
+            0x35 => self.line_end_valid = value == 0,
+            0x38 => self.tline_wrap.size.0 = value,
+            0x39 => self.tline_wrap.size.1 = value,
+            0x3a => self.tline_wrap.offset.0 = value,
+            0x3b => self.tline_wrap.offset.1 = value,
+            0x3c..=0x3d => self.line_end.0 = set_byte(self.line_end.0, offset - 0x3c),
+            0x3e..=0x3f => self.line_end.1 = set_byte(self.line_end.1, offset - 0x3e),
+            _ => {}
+        }
+    }
 
+    fn print_char(&mut self, char_data: &font::Character, x: i32, y: i32, color: Color) {
         for x_offset in 0..4_i32 {
             for y_offset in 0..6 {
                 let print = char_data[(x_offset + y_offset * 4) as usize] != 0;
 
                 if print {
-                    self.pset(x + x_offset, y + y_offset, color);
+                    self.pset_ignoring_fill_pattern(x + x_offset, y + y_offset, color);
                 }
             }
         }
@@ -119,13 +242,23 @@ impl DrawData {
         }
     }
 
+    /// Like `index`, but also discards pixels outside of the clipping rectangle.
+    /// Every drawing operation should go through this.
+    fn clipped_index(&self, x: i32, y: i32) -> Option<usize> {
+        if self.clip.contains(x, y) {
+            self.index(x, y)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn quarter_bresenham(
         &mut self,
         cx: i32,
         cy: i32,
         radius: i32,
         color: Color,
-        plot: fn(&mut Self, i32, i32, i32, i32, Color),
+        mut plot: impl FnMut(&mut Self, i32, i32, i32, i32, Color),
     ) {
         let mut x = radius;
         let mut y = 0;
@@ -154,7 +287,31 @@ impl DrawData {
 impl DrawData {
     pub(crate) fn pal(&mut self, c0: Color, c1: Color) {
         // https://pico-8.fandom.com/wiki/Pal
-        self.draw_palette[c0 as usize] = c1;
+        self.draw_palette[(c0 & 0x0f) as usize] = c1 & 0x0f;
+    }
+
+    /// Remaps `c0` to `c1` on the draw palette (`palette == 0`)
+    /// or on the screen palette (`palette == 1`). Other palettes are ignored.
+    ///
+    /// Only the screen palette can use the extended colors (128 to 143).
+    pub(crate) fn pal_(&mut self, c0: Color, c1: Color, palette: u8) {
+        match palette {
+            0 => self.pal(c0, c1),
+            1 => self.screen_palette[(c0 & 0x0f) as usize] = c1 & 0x8f,
+            _ => {}
+        }
+    }
+
+    /// Limits the screen palette to the given `(x, y, w, h)` screen areas,
+    /// or applies it to the whole screen again if `areas` is empty.
+    ///
+    /// This isn't part of pico8's API: the editor uses it to show the extended palette
+    /// in its swatches and canvas without recoloring the rest of its UI.
+    pub fn screen_palette_areas(&mut self, areas: &[(i32, i32, i32, i32)]) {
+        self.screen_palette_areas = areas
+            .iter()
+            .map(|&(x, y, w, h)| ClipRect::new(x, y, w, h))
+            .collect();
     }
 
     /// Sets the new camera. Returns the previous value set.
@@ -162,9 +319,42 @@ impl DrawData {
         std::mem::replace(&mut self.camera, (x, y))
     }
 
+    /// Sets the clipping rectangle, in screen coordinates (the camera doesn't affect it).
+    /// Returns the previous value set, as `(x, y, w, h)`.
+    pub(crate) fn clip(&mut self, x: i32, y: i32, w: i32, h: i32) -> (i32, i32, i32, i32) {
+        std::mem::replace(&mut self.clip, ClipRect::new(x, y, w, h)).to_xywh()
+    }
+
+    /// Resets the clipping rectangle to the whole screen.
+    /// Returns the previous value set, as `(x, y, w, h)`.
+    pub(crate) fn reset_clip(&mut self) -> (i32, i32, i32, i32) {
+        std::mem::replace(&mut self.clip, ClipRect::FULL_SCREEN).to_xywh()
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Fillp>
+    pub(crate) fn fillp(&mut self, pattern: u16, transparent: bool) {
+        self.fill_pattern = FillPattern {
+            pattern,
+            transparent,
+        };
+    }
+
+    /// Draws a pixel of a shape (`pset`, `line`, `rectfill`, etc.), which uses the fill pattern.
+    ///
+    /// `color`'s low nibble is the primary color, its high nibble is the secondary
+    /// color, used for the pixels whose bit is set in the pattern.
     pub(crate) fn pset(&mut self, x: i32, y: i32, color: Color) {
         let (x, y) = self.apply_camera(x, y);
-        if let Some(index) = self.index(x, y) {
+        if let Some(index) = self.clipped_index(x, y) {
+            if let Some(color) = self.fill_pattern.color_at(x, y, color) {
+                self.set_pixel(index, color);
+            }
+        }
+    }
+
+    fn pset_ignoring_fill_pattern(&mut self, x: i32, y: i32, color: Color) {
+        let (x, y) = self.apply_camera(x, y);
+        if let Some(index) = self.clipped_index(x, y) {
             self.set_pixel(index, color);
         }
     }
@@ -172,9 +362,7 @@ impl DrawData {
     pub(crate) fn pget(&self, x: i32, y: i32) -> Color {
         let (x, y) = self.apply_camera(x, y);
         if let Some(index) = self.index(x, y) {
-            let buffer_color = self.get_pixel(index);
-
-            get_pico8_color(buffer_color)
+            self.get_pixel(index)
         } else {
             0
         }
@@ -201,14 +389,108 @@ impl DrawData {
         }
     }
 
+    /// <https://pico-8.fandom.com/wiki/Tline>
+    ///
+    /// Draws a line textured with the map: each pixel takes its color from the map at
+    /// (`mx`, `my`), in cells, which advance by (`mdx`, `mdy`) after every pixel.
+    /// Like [`DrawData::map`], sprite 0 and sprites missing any flag of `layers` aren't drawn.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn tline(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        mut mx: Fixed,
+        mut my: Fixed,
+        mdx: Fixed,
+        mdy: Fixed,
+        layers: u8,
+        map: &Map,
+        flags: &Flags,
+        sprite_sheet: &SpriteSheet,
+    ) {
+        for (x, y) in draw::line(x0, y0, x1, y1) {
+            let (cell_x, cell_y) = self.tline_wrap.cell(mx, my);
+            let spr = map.mget(sprite_sheet, cell_x, cell_y);
+            let sprite_flags = flags.get(spr.into()).unwrap_or(0);
+
+            if spr != 0 && sprite_flags & layers == layers {
+                // Pixel of the sprite under (mx, my).
+                let pixel = |coordinate: Fixed| (i32::from(coordinate << 3) & 7) as usize;
+                let spr = spr as usize;
+                let sheet_x = spr % SpriteSheet::SPRITES_PER_ROW * Sprite::WIDTH + pixel(mx);
+                let sheet_y = spr / SpriteSheet::SPRITES_PER_ROW * Sprite::HEIGHT + pixel(my);
+
+                let (x, y) = self.apply_camera(x, y);
+                if let (Some(color), Some(index)) =
+                    (sprite_sheet.get(sheet_x, sheet_y), self.clipped_index(x, y))
+                {
+                    self.set_pixel_with_transparency(index, color);
+                }
+            }
+
+            mx += mdx;
+            my += mdy;
+        }
+    }
+
+    /// Draws a line from where the last one ended to (`x`, `y`), with the pen color.
+    /// If there's no previous line (see [`DrawData::set_line_end`]), it only moves the end.
+    pub(crate) fn line_to(&mut self, x: i32, y: i32) {
+        if self.line_end_valid {
+            let (x0, y0) = self.line_end;
+            self.line(x0, y0, x, y, self.pen_color);
+        }
+
+        self.set_line_end(Some((x, y)));
+    }
+
+    /// Sets where [`DrawData::line_to`] continues from, `None` to start a new line
+    /// (keeping the old end in the registers).
+    pub(crate) fn set_line_end(&mut self, line_end: Option<(i32, i32)>) {
+        if let Some(line_end) = line_end {
+            self.line_end = line_end;
+        }
+        self.line_end_valid = line_end.is_some();
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Color>
+    ///
+    /// Sets the pen color, returns the previous one.
+    pub(crate) fn color(&mut self, color: Color) -> Color {
+        std::mem::replace(&mut self.pen_color, color)
+    }
+
+    pub(crate) fn pen_color(&self) -> Color {
+        self.pen_color
+    }
+
     pub(crate) fn reset_pal(&mut self) {
         self.draw_palette = ORIGINAL_PALETTE;
+        self.screen_palette = ORIGINAL_PALETTE;
         // pal() resets transparency to default
-        self.palt(Some(0));
+        self.transparency = DEFAULT_TRANSPARENCY;
     }
 
+    /// Makes `transparent_color` the only transparent color (or none of them).
     pub(crate) fn palt(&mut self, transparent_color: Option<Color>) {
-        self.transparent_color = transparent_color
+        self.transparency = transparent_color.map_or(0, transparency_bit);
+    }
+
+    /// Sets whether `color` is transparent, leaving the other colors as they are.
+    pub(crate) fn palt_(&mut self, color: Color, transparent: bool) {
+        if transparent {
+            self.transparency |= transparency_bit(color);
+        } else {
+            self.transparency &= !transparency_bit(color);
+        }
+    }
+
+    /// Sets the transparency of every color at once (bit `15 - c` for color `c`).
+    /// Returns the previous mask.
+    pub(crate) fn palt_mask(&mut self, mask: u16) -> u16 {
+        std::mem::replace(&mut self.transparency, mask)
     }
 
     // Taken from Pemsa, a C++ implementation of pico8.
@@ -252,45 +534,353 @@ impl DrawData {
         self.quarter_bresenham(cx, cy, radius, color, plot);
     }
 
-    pub(crate) fn print(&mut self, str: &str, x: i32, y: i32, color: Color) {
-        for (pos, char) in str.chars().enumerate() {
-            let index = char as u8 - font::FIRST_CHAR as u8;
+    /// <https://pico-8.fandom.com/wiki/Oval>
+    ///
+    /// Draws the outline of the ellipse that fits in the rectangle from (`x0`, `y0`) to
+    /// (`x1`, `y1`), both included.
+    pub(crate) fn oval(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.ellipse(x0, y0, x1, y1, |this, left, right, y| {
+            this.pset(left, y, color);
+            this.pset(right, y, color);
+        });
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Ovalfill>
+    pub(crate) fn ovalfill(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.ellipse(x0, y0, x1, y1, |this, left, right, y| {
+            this.line(left, y, right, y, color);
+        });
+    }
+
+    // Alois Zingl's ellipse inside a rectangle, which handles even widths and heights.
+    // It walks the 4 quadrants at once, calling `plot` with the left and right points of a row.
+    // Pico8 doesn't document its own, so ovals may differ from it by a pixel here and there.
+    //
+    // See: http://members.chello.at/easyfilter/bresenham.html
+    fn ellipse(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        mut plot: impl FnMut(&mut Self, i32, i32, i32),
+    ) {
+        let MinMax {
+            min: mut x0,
+            max: mut x1,
+        } = min_max(x0, x1);
+        let MinMax { min: y0, max: y1 } = min_max(y0, y1);
 
-            self.print_char(index as usize, x + (pos * 4) as i32, y, color);
+        let a = (x1 - x0) as i64;
+        let b = (y1 - y0) as i64;
+        let b1 = b & 1;
+        let mut dx = 4 * (1 - a) * b * b;
+        let mut dy = 4 * (b1 + 1) * a * a;
+        let mut error = dx + dy + b1 * a * a;
+
+        // Rows below and above the center, they move away from it.
+        let mut bottom = y0 + ((b + 1) / 2) as i32;
+        let mut top = bottom - b1 as i32;
+        let (a8, b8) = (8 * a * a, 8 * b * b);
+
+        while x0 <= x1 {
+            plot(self, x0, x1, bottom);
+            plot(self, x0, x1, top);
+
+            let e2 = 2 * error;
+            if e2 <= dy {
+                bottom += 1;
+                top -= 1;
+                dy += a8;
+                error += dy;
+            }
+            if e2 >= dx || 2 * error > dy {
+                x0 += 1;
+                x1 -= 1;
+                dx += b8;
+                error += dx;
+            }
         }
+
+        // Flat ellipses stop early, finish their tips.
+        while ((bottom - top) as i64) <= b {
+            plot(self, x0 - 1, x1 + 1, bottom);
+            plot(self, x0 - 1, x1 + 1, top);
+            bottom += 1;
+            top -= 1;
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rrect>
+    ///
+    /// Draws the outline of a `width` by `height` rectangle at (`x`, `y`), with corners rounded
+    /// with `radius`. The radius is limited to half of the shortest side.
+    pub(crate) fn rrect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        radius: i32,
+        color: Color,
+    ) {
+        let Some(corners) = RoundedCorners::new(x, y, width, height, radius) else {
+            return;
+        };
+        let RoundedCorners {
+            left,
+            right,
+            top,
+            bottom,
+            radius,
+        } = corners;
+
+        self.quarter_bresenham(0, 0, radius, color, |this, _, _, dx, dy, color| {
+            for (dx, dy) in [(dx, dy), (dy, dx)] {
+                this.pset(left - dx, top - dy, color);
+                this.pset(right + dx, top - dy, color);
+                this.pset(left - dx, bottom + dy, color);
+                this.pset(right + dx, bottom + dy, color);
+            }
+        });
+        self.line(left, y, right, y, color);
+        self.line(left, y + height - 1, right, y + height - 1, color);
+        self.line(x, top, x, bottom, color);
+        self.line(x + width - 1, top, x + width - 1, bottom, color);
     }
 
+    /// <https://pico-8.fandom.com/wiki/Rrectfill>
+    pub(crate) fn rrectfill(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        radius: i32,
+        color: Color,
+    ) {
+        let Some(corners) = RoundedCorners::new(x, y, width, height, radius) else {
+            return;
+        };
+        let RoundedCorners {
+            left,
+            right,
+            top,
+            bottom,
+            radius,
+        } = corners;
+
+        self.quarter_bresenham(0, 0, radius, color, |this, _, _, dx, dy, color| {
+            for (dx, dy) in [(dx, dy), (dy, dx)] {
+                this.line(left - dx, top - dy, right + dx, top - dy, color);
+                this.line(left - dx, bottom + dy, right + dx, bottom + dy, color);
+            }
+        });
+        self.rectfill(x, top, x + width - 1, bottom, color);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Print>
+    ///
+    /// Handles newlines, tabs and the [`font::control`] codes.
+    /// Sets the pen color to `color` and moves the text cursor to the line after the text.
+    /// Returns the right-most x coordinate the text reached.
+    pub(crate) fn print(&mut self, str: &str, x: i32, y: i32, color: Color) -> i32 {
+        fn param(chars: &mut std::str::Chars) -> Option<i32> {
+            chars.next()?.to_digit(32).map(|digit| digit as i32)
+        }
+        fn color_param(chars: &mut std::str::Chars) -> Option<Color> {
+            param(chars)
+                .filter(|&color| color < 16)
+                .map(|color| color as Color)
+        }
+
+        self.pen_color = color;
+        let (mut cursor_x, mut cursor_y) = (x, y);
+        let mut right_most = x;
+        let mut foreground = color;
+        let mut background = None;
+        let mut repeat = 1;
+
+        let mut chars = str.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '\0' => break,
+                '\n' => {
+                    cursor_x = x;
+                    cursor_y += font::LINE_HEIGHT;
+                }
+                font::control::CARRIAGE_RETURN => cursor_x = x,
+                '\t' => {
+                    let column = (cursor_x - x).div_euclid(font::TAB_WIDTH) + 1;
+                    cursor_x = x + column * font::TAB_WIDTH;
+                }
+                font::control::BACKSPACE => cursor_x -= font::CHAR_WIDTH,
+                font::control::REPEAT => repeat = param(&mut chars).unwrap_or(1),
+                font::control::FOREGROUND => {
+                    foreground = color_param(&mut chars).unwrap_or(foreground);
+                }
+                font::control::BACKGROUND => background = color_param(&mut chars),
+                font::control::MOVE_X => cursor_x += param(&mut chars).unwrap_or(16) - 16,
+                font::control::MOVE_Y => cursor_y += param(&mut chars).unwrap_or(16) - 16,
+                font::control::MOVE => {
+                    cursor_x += param(&mut chars).unwrap_or(16) - 16;
+                    cursor_y += param(&mut chars).unwrap_or(16) - 16;
+                }
+                char => {
+                    if let Some(glyph) = font::glyph(char) {
+                        for _ in 0..repeat {
+                            if let Some(background) = background {
+                                self.print_background(cursor_x, cursor_y, background);
+                            }
+                            self.print_char(glyph, cursor_x, cursor_y, foreground);
+
+                            cursor_x += font::CHAR_WIDTH;
+                            right_most = right_most.max(cursor_x);
+                        }
+                    }
+                    repeat = 1;
+                }
+            }
+        }
+
+        self.cursor = (x, cursor_y + font::LINE_HEIGHT);
+
+        right_most
+    }
+
+    /// Like [`DrawData::print`], at the text cursor and with the pen color.
+    pub(crate) fn print_at_cursor(&mut self, str: &str) -> i32 {
+        let (x, y) = self.cursor;
+
+        self.print(str, x, y, self.pen_color)
+    }
+
+    /// Moves the text cursor, returns its previous position.
+    pub(crate) fn cursor(&mut self, x: i32, y: i32) -> (i32, i32) {
+        std::mem::replace(&mut self.cursor, (x, y))
+    }
+
+    /// Background behind a character: its cell, plus a pixel to the left and above.
+    fn print_background(&mut self, x: i32, y: i32, color: Color) {
+        for x in x - 1..x + font::CHAR_WIDTH {
+            for y in y - 1..y + font::LINE_HEIGHT {
+                self.pset_ignoring_fill_pattern(x, y, color);
+            }
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Spr>
+    ///
+    /// Draws a block of `w` by `h` sprites, starting at sprite `spr` on the sprite sheet.
+    /// Fractional sizes draw part of the block (`w = 0.5` is 4 pixels wide).
+    /// Flipping mirrors the whole block, not each sprite.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn spr_(
+        &mut self,
+        sprite_sheet: &SpriteSheet,
+        spr: usize,
+        x: i32,
+        y: i32,
+        w: f32,
+        h: f32,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        let sheet_x = (spr % SpriteSheet::SPRITES_PER_ROW * Sprite::WIDTH) as i32;
+        let sheet_y = (spr / SpriteSheet::SPRITES_PER_ROW * Sprite::HEIGHT) as i32;
+        let width = (w * Sprite::WIDTH as f32) as i32;
+        let height = (h * Sprite::HEIGHT as f32) as i32;
+
+        self.blit(x, y, width, height, flip_x, flip_y, |i, j| {
+            sprite_sheet.get(
+                usize::try_from(sheet_x + i).ok()?,
+                usize::try_from(sheet_y + j).ok()?,
+            )
+        });
+    }
+
+    pub(crate) fn spr(&mut self, sprite_sheet: &SpriteSheet, spr: usize, x: i32, y: i32) {
+        self.spr_(sprite_sheet, spr, x, y, 1.0, 1.0, false, false)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Sspr>
+    ///
+    /// Stretches the `sw` by `sh` rectangle of the sprite sheet at (`sx`, `sy`)
+    /// into a `dw` by `dh` rectangle of the screen at (`dx`, `dy`), using nearest-neighbor scaling.
     #[allow(clippy::too_many_arguments)]
-    // TODO: Implement w and h params functionality
-    pub fn spr_(
+    pub(crate) fn sspr(
+        &mut self,
+        sprite_sheet: &SpriteSheet,
+        sx: i32,
+        sy: i32,
+        sw: i32,
+        sh: i32,
+        dx: i32,
+        dy: i32,
+        dw: i32,
+        dh: i32,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        self.blit(dx, dy, dw, dh, flip_x, flip_y, |i, j| {
+            sprite_sheet.get(
+                usize::try_from(sx + i * sw / dw).ok()?,
+                usize::try_from(sy + j * sh / dh).ok()?,
+            )
+        });
+    }
+
+    /// Like [`DrawData::spr_`], for a sprite that isn't on the game's sprite sheet.
+    /// The block can't be larger than the sprite itself.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn raw_spr(
         &mut self,
         sprite: &Sprite,
         x: i32,
         y: i32,
-        _w: f32,
-        _h: f32,
+        w: f32,
+        h: f32,
         flip_x: bool,
         flip_y: bool,
     ) {
-        let buffer = &sprite.sprite;
+        let width = ((w * Sprite::WIDTH as f32) as i32).min(Sprite::WIDTH as i32);
+        let height = ((h * Sprite::HEIGHT as f32) as i32).min(Sprite::HEIGHT as i32);
 
-        for i in 0..8 {
-            for j in 0..8 {
-                let world_x = if flip_x { x + 7 - i } else { x + i };
-                let world_y = if flip_y { y + 7 - j } else { y + j };
+        self.blit(x, y, width, height, flip_x, flip_y, |i, j| {
+            Some(sprite.pget(i as isize, j as isize))
+        });
+    }
 
-                let (x, y) = self.apply_camera(world_x, world_y);
-                if let Some(index) = self.index(x, y) {
-                    self.set_pixel_with_transparency(index, buffer[(i + j * 8) as usize])
+    /// Draws a `width` by `height` block of pixels at (`x`, `y`),
+    /// `source` gives the color of each pixel of the block (`None` skips it).
+    #[allow(clippy::too_many_arguments)]
+    fn blit(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        flip_x: bool,
+        flip_y: bool,
+        source: impl Fn(i32, i32) -> Option<Color>,
+    ) {
+        for i in 0..width {
+            for j in 0..height {
+                let source_x = if flip_x { width - 1 - i } else { i };
+                let source_y = if flip_y { height - 1 - j } else { j };
+
+                let Some(color) = source(source_x, source_y) else {
+                    continue;
+                };
+
+                let (x, y) = self.apply_camera(x + i, y + j);
+                if let Some(index) = self.clipped_index(x, y) {
+                    self.set_pixel_with_transparency(index, color)
                 }
             }
         }
     }
 
-    pub(crate) fn spr(&mut self, sprite: &Sprite, x: i32, y: i32) {
-        self.spr_(sprite, x, y, 1.0, 1.0, false, false)
-    }
-
     pub(crate) fn cls_color(&mut self, color: Color) {
         let (start_x, start_y) = self.unapply_camera(0, 0);
         let (end_x, end_y) = self.unapply_camera(127, 127);
@@ -299,36 +889,160 @@ impl DrawData {
     }
 
     /// <https://pico-8.fandom.com/wiki/Map>
-    #[allow(clippy::too_many_arguments)]
+    ///
+    /// Sprite 0 is never drawn. With a non-zero `layer`, only sprites
+    /// that have every flag set in `layer` are drawn.
     pub(crate) fn map(
         &mut self,
-        cell_x: i32,
-        cell_y: i32,
-        screen_x: i32,
-        screen_y: i32,
-        cell_w: i32,
-        cell_h: i32,
-        // TODO: Use layer
-        layer: u8,
+        options: MapOptions,
         map: &Map,
         flags: &Flags,
         sprite_sheet: &SpriteSheet,
     ) {
-        // TODO: Handle like pico8
-        for (i_x, map_x) in (cell_x..=(cell_x + cell_w)).enumerate() {
-            for (i_y, map_y) in (cell_y..=(cell_y + cell_h)).enumerate() {
-                let spr = map.mget(map_x, map_y);
+        let MapOptions {
+            cell_x,
+            cell_y,
+            screen_x,
+            screen_y,
+            cell_w,
+            cell_h,
+            layer,
+        } = options;
+
+        for i_x in 0..cell_w {
+            for i_y in 0..cell_h {
+                let spr = map.mget(sprite_sheet, cell_x + i_x, cell_y + i_y);
+                let sprite_flags = flags.get(spr.into()).unwrap_or(0);
+
+                if spr != 0 && sprite_flags & layer == layer {
+                    let x = screen_x + 8 * i_x;
+                    let y = screen_y + 8 * i_y;
+
+                    self.spr(sprite_sheet, spr as usize, x, y);
+                }
+            }
+        }
+    }
+}
+
+/// Region of the screen that drawing operations can modify.
+/// Covers the pixels in `x0..x1` and `y0..y1`, always inside the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ClipRect {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
 
-                let flags = flags.get(spr.into()).unwrap();
+impl ClipRect {
+    const FULL_SCREEN: Self = Self {
+        x0: 0,
+        y0: 0,
+        x1: WIDTH as i32,
+        y1: WIDTH as i32,
+    };
 
-                if flags & layer == layer {
-                    let x = screen_x + 8 * i_x as i32;
-                    let y = screen_y + 8 * i_y as i32;
+    /// <https://pico-8.fandom.com/wiki/Clip>
+    fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        let clamp = |n: i32| n.clamp(0, WIDTH as i32);
+        let (x0, y0) = (clamp(x), clamp(y));
 
-                    let spr = sprite_sheet.get_sprite(spr as usize);
-                    self.spr(spr, x, y);
-                }
-            }
+        Self {
+            x0,
+            y0,
+            x1: clamp(x.saturating_add(w)).max(x0),
+            y1: clamp(y.saturating_add(h)).max(y0),
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        self.x0 <= x && x < self.x1 && self.y0 <= y && y < self.y1
+    }
+
+    fn to_xywh(self) -> (i32, i32, i32, i32) {
+        (self.x0, self.y0, self.x1 - self.x0, self.y1 - self.y0)
+    }
+}
+
+/// Centers of the corners of a rounded rectangle (see [`DrawData::rrect`]).
+#[derive(Clone, Copy, Debug)]
+struct RoundedCorners {
+    left: i32,
+    right: i32,
+    top: i32,
+    bottom: i32,
+    radius: i32,
+}
+
+impl RoundedCorners {
+    /// `None` for empty rectangles.
+    fn new(x: i32, y: i32, width: i32, height: i32, radius: i32) -> Option<Self> {
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        let radius = radius.clamp(0, (width.min(height) - 1) / 2);
+
+        Some(Self {
+            left: x + radius,
+            right: x + width - 1 - radius,
+            top: y + radius,
+            bottom: y + height - 1 - radius,
+            radius,
+        })
+    }
+}
+
+/// Region of the map that `tline` repeats, like pico8's `0x5f38..0x5f3c` registers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct TlineWrap {
+    /// Width and height of the region, in cells. 0 means 256, which doesn't wrap inside the map.
+    size: (u8, u8),
+    /// Cell where the region starts.
+    offset: (u8, u8),
+}
+
+impl TlineWrap {
+    /// Map cell under map coordinates (`mx`, `my`).
+    fn cell(self, mx: Fixed, my: Fixed) -> (i32, i32) {
+        let wrap = |coordinate: Fixed, size: u8, offset: u8| {
+            let size = if size == 0 { 256 } else { size as i32 };
+
+            i32::from(coordinate).rem_euclid(size) + offset as i32
+        };
+
+        (
+            wrap(mx, self.size.0, self.offset.0),
+            wrap(my, self.size.1, self.offset.1),
+        )
+    }
+}
+
+/// A 4x4 pattern used to fill shapes, see <https://pico-8.fandom.com/wiki/Fillp>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FillPattern {
+    /// One bit per pixel, the most significant one being the top-left pixel of the 4x4 tile.
+    pattern: u16,
+    /// Whether pixels whose bit is set are skipped, instead of drawn in the secondary color.
+    transparent: bool,
+}
+
+impl FillPattern {
+    const SOLID: Self = Self {
+        pattern: 0,
+        transparent: false,
+    };
+
+    /// Color for a pixel at screen coordinates `(x, y)`, or `None` if it's transparent.
+    fn color_at(self, x: i32, y: i32, color: Color) -> Option<Color> {
+        let bit = 15 - ((x & 3) + 4 * (y & 3));
+
+        if self.pattern & (1 << bit) == 0 {
+            Some(color & 0x0F)
+        } else if self.transparent {
+            None
+        } else {
+            Some(color >> 4)
         }
     }
 }
@@ -339,18 +1053,22 @@ impl Default for DrawData {
     }
 }
 
-fn get_color(index: Color) -> u32 {
-    COLORS[index as usize]
+fn transparency_bit(color: Color) -> u16 {
+    0x8000 >> (color & 0x0f)
 }
 
-fn get_pico8_color(buffer_color: u32) -> Color {
-    for (index, color) in COLORS.into_iter().enumerate() {
-        if buffer_color == color {
-            return index as u8;
-        }
-    }
+/// Byte of the [`Buffer`] holding a pixel, and the shift of its 4 bits in it.
+fn pixel_location(index: usize) -> (usize, u8) {
+    (index / PIXELS_PER_BYTE, 4 * (index % PIXELS_PER_BYTE) as u8)
+}
 
-    panic!("Couldn't convert buffer color {buffer_color:#x} to pico8 color");
+/// RGB value of a pico8 color, 0 to 15 or (extended) 128 to 143.
+pub(crate) fn get_color(index: Color) -> u32 {
+    if index & EXTENDED_COLORS_START != 0 {
+        EXTENDED_COLORS[(index & 0x0f) as usize]
+    } else {
+        COLORS[(index & 0x0f) as usize]
+    }
 }
 
 // Add _FF at the end for alpha
@@ -373,6 +1091,30 @@ pub const COLORS: [u32; 16] = [
     0xFFCCAA, // Light peach
 ];
 
+/// Pico8's "secret" palette, colors 128 to 143.
+/// They can only be shown through the screen palette: `pal(c, 128 + i, 1)`.
+pub const EXTENDED_COLORS: [u32; 16] = [
+    0x291814, // Darkest grey
+    0x111D35, // Darker blue
+    0x422136, // Darker purple
+    0x125359, // Blue green
+    0x742F29, // Dark brown
+    0x49333B, // Darker grey
+    0xA28879, // Medium grey
+    0xF3EF7D, // Light yellow
+    0xBE1250, // Dark red
+    0xFF6C24, // Dark orange
+    0xA8E72E, // Lime green
+    0x00B543, // Medium green
+    0x065AB5, // True blue
+    0x754665, // Mauve
+    0xFF6E59, // Dark peach
+    0xFF9D81, // Peach
+];
+
+/// First extended color, see [`EXTENDED_COLORS`].
+pub const EXTENDED_COLORS_START: Color = 128;
+
 /// Named constants for the colors in the Pico8 palette.
 pub mod colors {
     use crate::Color;
@@ -393,6 +1135,13 @@ pub mod colors {
     pub const LAVENDER: Color = 13;
     pub const PINK: Color = 14;
     pub const LIGHT_PEACH: Color = 15;
+
+    /// Red, green and blue components of a color, 0 to 15 or (extended) 128 to 143.
+    pub fn rgb(color: Color) -> [u8; 3] {
+        let [_, r, g, b] = super::get_color(color).to_be_bytes();
+
+        [r, g, b]
+    }
 }
 
 #[cfg(test)]
@@ -401,10 +1150,11 @@ mod tests {
 
     use crate::{
         colors,
-        draw_data::{get_color, Buffer, NUM_COMPONENTS, ORIGINAL_PALETTE},
+        draw_data::{Buffer, ORIGINAL_PALETTE},
+        Fixed, Flags, Map, MapOptions, SpriteSheet,
     };
 
-    use super::DrawData;
+    use super::{DrawData, WIDTH};
 
     #[derive(Clone)]
     enum IterBothNextYield {
@@ -451,21 +1201,9 @@ mod tests {
     #[test]
     fn rectfill_works_with_unordered_arguments() {
         fn red_pixels_count(buf: &Buffer) -> usize {
-            fn is_red(chunk: &[u8]) -> bool {
-                let chunk: Vec<u32> = chunk
-                    .into_iter()
-                    .copied()
-                    .map(|component| component as u32)
-                    .collect();
-                let (r, g, b) = (chunk[0], chunk[1], chunk[2]);
-
-                let color = r << 16 | g << 8 | b;
-
-                color == get_color(colors::RED)
-            }
-
-            buf.chunks(NUM_COMPONENTS)
-                .filter(|chunk| is_red(chunk))
+            buf.iter()
+                .flat_map(|byte| [byte & 0x0f, byte >> 4])
+                .filter(|&color| color == colors::RED)
                 .count()
         }
 
@@ -568,4 +1306,988 @@ mod tests {
             assert_eq!(draw_data.pget(x, y), color);
         }
     }
+
+    /// Screen coordinates of every pixel that isn't black.
+    fn drawn_pixels(draw_data: &DrawData) -> Vec<(i32, i32)> {
+        (0..128)
+            .flat_map(|y| (0..128).map(move |x| (x, y)))
+            .filter(|&(x, y)| draw_data.pget(x, y) != colors::BLACK)
+            .collect()
+    }
+
+    #[test]
+    fn clip_restricts_every_primitive() {
+        let mut sprite_sheet = SpriteSheet::new();
+        for x in 8..16 {
+            for y in 0..8 {
+                sprite_sheet.set(x, y, colors::RED);
+            }
+        }
+        let map = Map::new();
+        let flags = Flags::new();
+
+        let primitives: [&dyn Fn(&mut DrawData); 9] = [
+            &|draw_data| {
+                for x in 0..128 {
+                    for y in 0..128 {
+                        draw_data.pset(x, y, colors::RED);
+                    }
+                }
+            },
+            &|draw_data| draw_data.line(0, 0, 127, 127, colors::RED),
+            &|draw_data| draw_data.rectfill(0, 0, 127, 127, colors::RED),
+            &|draw_data| draw_data.circfill(64, 64, 100, colors::RED),
+            &|draw_data| {
+                draw_data.spr_(&sprite_sheet, 1, 8, 8, 1.0, 1.0, false, false);
+            },
+            &|draw_data| {
+                let options = MapOptions {
+                    screen_x: 4,
+                    screen_y: 8,
+                    cell_w: 16,
+                    cell_h: 16,
+                    ..MapOptions::default()
+                };
+                draw_data.map(options, &map, &flags, &sprite_sheet)
+            },
+            &|draw_data| {
+                draw_data.print("HELLO WORLD", 4, 10, colors::RED);
+            },
+            &|draw_data| draw_data.cls_color(colors::RED),
+            &|draw_data| {
+                let (mx, mdx) = (Fixed::from(1), Fixed::from(0.125));
+                for y in 0..128 {
+                    draw_data.tline(
+                        0,
+                        y,
+                        127,
+                        y,
+                        mx,
+                        Fixed::ZERO,
+                        mdx,
+                        Fixed::ZERO,
+                        0,
+                        &map,
+                        &flags,
+                        &sprite_sheet,
+                    );
+                }
+            },
+        ];
+
+        for primitive in primitives {
+            let mut draw_data = DrawData::new();
+            draw_data.clip(10, 12, 5, 3);
+
+            primitive(&mut draw_data);
+
+            let pixels = drawn_pixels(&draw_data);
+            assert!(!pixels.is_empty());
+            for (x, y) in pixels {
+                assert!((10..15).contains(&x) && (12..15).contains(&y));
+            }
+        }
+    }
+
+    #[test]
+    fn clip_is_in_screen_space() {
+        let mut draw_data = DrawData::new();
+        draw_data.camera(-20, -20);
+        draw_data.clip(0, 0, 4, 4);
+
+        draw_data.rectfill(-20, -20, 107, 107, colors::RED);
+
+        let pixels = drawn_pixels(&DrawData {
+            camera: (0, 0),
+            ..draw_data
+        });
+        assert_eq!(pixels.len(), 16);
+        assert!(pixels.iter().all(|&(x, y)| x < 4 && y < 4));
+    }
+
+    #[test]
+    fn clip_returns_previous_clip() {
+        let mut draw_data = DrawData::new();
+
+        assert_eq!(draw_data.clip(10, 20, 30, 40), (0, 0, 128, 128));
+        assert_eq!(draw_data.clip(0, 0, 1, 1), (10, 20, 30, 40));
+        assert_eq!(draw_data.reset_clip(), (0, 0, 1, 1));
+        assert_eq!(draw_data.reset_clip(), (0, 0, 128, 128));
+    }
+
+    #[test]
+    fn clip_is_clamped_to_the_screen() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.clip(-10, -20, 30, 40);
+        assert_eq!(draw_data.reset_clip(), (0, 0, 20, 20));
+
+        draw_data.clip(120, 100, 30, 10);
+        assert_eq!(draw_data.reset_clip(), (120, 100, 8, 10));
+
+        draw_data.clip(10, 10, -5, -5);
+        assert_eq!(draw_data.reset_clip(), (10, 10, 0, 0));
+    }
+
+    #[test]
+    fn reset_clip_allows_drawing_everywhere_again() {
+        let mut draw_data = DrawData::new();
+        draw_data.clip(0, 0, 0, 0);
+        draw_data.rectfill(0, 0, 127, 127, colors::RED);
+        assert!(drawn_pixels(&draw_data).is_empty());
+
+        draw_data.reset_clip();
+        draw_data.rectfill(0, 0, 127, 127, colors::RED);
+        assert_eq!(drawn_pixels(&draw_data).len(), 128 * 128);
+    }
+
+    /// Renders a region of the screen as one string per row, one hex digit per pixel.
+    fn screen_region(draw_data: &DrawData, x: i32, y: i32, w: i32, h: i32) -> Vec<String> {
+        (y..y + h)
+            .map(|y| {
+                (x..x + w)
+                    .map(|x| format!("{:x}", draw_data.pget(x, y)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fillp_uses_secondary_color_for_set_bits() {
+        let mut draw_data = DrawData::new();
+        draw_data.fillp(0b1000_0100_0010_0001, false);
+
+        draw_data.rectfill(0, 0, 7, 5, 0x4e);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 8, 6),
+            [
+                "4eee4eee", //
+                "e4eee4ee", //
+                "ee4eee4e", //
+                "eee4eee4", //
+                "4eee4eee", //
+                "e4eee4ee", //
+            ]
+        );
+    }
+
+    #[test]
+    fn fillp_transparency_skips_set_bits() {
+        let mut draw_data = DrawData::new();
+        draw_data.rectfill(0, 0, 7, 3, colors::BLUE);
+        draw_data.fillp(0b1100_1100_0011_0011, true);
+
+        draw_data.rectfill(0, 0, 7, 3, colors::RED);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 8, 4),
+            [
+                "cc88cc88", //
+                "cc88cc88", //
+                "88cc88cc", //
+                "88cc88cc", //
+            ]
+        );
+    }
+
+    #[test]
+    fn fillp_is_aligned_to_the_screen() {
+        let mut draw_data = DrawData::new();
+        draw_data.fillp(0b1010_0101_1010_0101, false);
+        draw_data.camera(-1, 0);
+
+        draw_data.line(0, 0, 5, 0, 0x17);
+        draw_data.pset(0, 1, 0x17);
+        draw_data.pset(1, 1, 0x17);
+        draw_data.camera(0, 0);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 7, 2),
+            [
+                "0717171", //
+                "0170000", //
+            ]
+        );
+    }
+
+    #[test]
+    fn fillp_applies_to_circfill_and_cls() {
+        let mut draw_data = DrawData::new();
+        draw_data.fillp(0b0000_0000_0000_1111, false);
+
+        draw_data.cls_color(0x9a);
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 4, 4),
+            ["aaaa", "aaaa", "aaaa", "9999"]
+        );
+
+        draw_data.cls_color(0);
+        draw_data.circfill(2, 2, 2, 0x38);
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 5, 5),
+            [
+                "08880", //
+                "88888", //
+                "88888", //
+                "33333", //
+                "08880", //
+            ]
+        );
+    }
+
+    #[test]
+    fn fillp_does_not_affect_print() {
+        let mut draw_data = DrawData::new();
+        draw_data.fillp(0xffff, true);
+
+        draw_data.print("a", 0, 0, colors::WHITE);
+        draw_data.rectfill(0, 0, 127, 127, colors::WHITE);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 4, 2), ["7770", "7070"]);
+    }
+
+    /// Sprites 1 and 2 have a red and orange left column, respectively.
+    /// Every other pixel of sprites 1, 2, 17 and 18 has the color 1, 2, 3 and 4.
+    fn block_sprite_sheet() -> SpriteSheet {
+        let mut sprite_sheet = SpriteSheet::new();
+        for (sprite, color) in [(1, 1), (2, 2), (17, 3), (18, 4)] {
+            let sprite = sprite_sheet.get_sprite_mut(sprite);
+            for x in 0..8 {
+                for y in 0..8 {
+                    sprite.pset(x, y, color);
+                }
+            }
+        }
+        for y in 0..8 {
+            sprite_sheet.get_sprite_mut(1).pset(0, y, colors::RED);
+            sprite_sheet.get_sprite_mut(2).pset(0, y, colors::ORANGE);
+        }
+
+        sprite_sheet
+    }
+
+    #[test]
+    fn spr_draws_blocks_of_adjacent_sprites() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+
+        draw_data.spr_(&sprite_sheet, 1, 0, 0, 2.0, 2.0, false, false);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 7, 17, 3),
+            [
+                "81111111922222220",
+                "33333333444444440",
+                "33333333444444440"
+            ]
+        );
+        assert_eq!(
+            screen_region(&draw_data, 0, 15, 17, 2),
+            ["33333333444444440", "00000000000000000"]
+        );
+    }
+
+    #[test]
+    fn spr_supports_fractional_sizes() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+
+        draw_data.spr_(&sprite_sheet, 1, 0, 0, 1.5, 0.5, false, false);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 13, 5),
+            [
+                "8111111192220",
+                "8111111192220",
+                "8111111192220",
+                "8111111192220",
+                "0000000000000"
+            ]
+        );
+    }
+
+    #[test]
+    fn spr_flips_the_whole_block() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+
+        draw_data.spr_(&sprite_sheet, 1, 1, 0, 2.0, 2.0, true, true);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 18, 1),
+            ["044444444333333330"]
+        );
+        assert_eq!(
+            screen_region(&draw_data, 0, 15, 18, 2),
+            ["022222229111111180", "000000000000000000"]
+        );
+    }
+
+    #[test]
+    fn spr_block_honors_camera_and_transparency() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+        draw_data.rectfill(0, 0, 127, 127, colors::WHITE);
+        draw_data.palt(Some(2));
+        draw_data.camera(-4, -1);
+
+        draw_data.spr_(&sprite_sheet, 1, 0, 0, 2.0, 1.0, false, false);
+
+        draw_data.camera(0, 0);
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 22, 2),
+            ["7777777777777777777777", "7777811111119777777777"]
+        );
+    }
+
+    #[test]
+    fn sspr_stretches_sprite_sheet_rectangles() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+
+        // Right half of sprite 1, and left half of sprite 2, at double size.
+        draw_data.sspr(&sprite_sheet, 12, 0, 6, 1, 0, 0, 12, 2, false, false);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 13, 3),
+            ["1111111199220", "1111111199220", "0000000000000"]
+        );
+    }
+
+    #[test]
+    fn sspr_shrinks_and_flips() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+
+        // Sprites 1 and 2, at half size.
+        draw_data.sspr(&sprite_sheet, 8, 0, 16, 16, 0, 0, 8, 8, true, false);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 9, 1), ["222911180"]);
+        assert_eq!(screen_region(&draw_data, 0, 4, 9, 1), ["444433330"]);
+    }
+
+    #[test]
+    fn sspr_honors_palette_transparency_and_camera() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+        draw_data.rectfill(0, 0, 127, 127, colors::WHITE);
+        draw_data.pal(1, colors::GREEN);
+        draw_data.palt(Some(colors::RED));
+        draw_data.camera(-2, 0);
+
+        draw_data.sspr(&sprite_sheet, 8, 0, 4, 1, 0, 0, 4, 1, false, false);
+
+        draw_data.camera(0, 0);
+        assert_eq!(screen_region(&draw_data, 0, 0, 7, 1), ["777bbb7"]);
+    }
+
+    fn map_options(cell_w: i32, cell_h: i32, layer: u8) -> MapOptions {
+        MapOptions {
+            cell_w,
+            cell_h,
+            layer,
+            ..MapOptions::default()
+        }
+    }
+
+    /// Cells (0, 0), (1, 0), (0, 1) and (1, 1) hold sprites 1, 2, 17 and 18.
+    fn block_map() -> Map {
+        let mut map = Map::new();
+        // Only the bottom half of the map is in the sprite sheet.
+        let unused = &mut SpriteSheet::new();
+        map.mset(unused, 0, 0, 1);
+        map.mset(unused, 1, 0, 2);
+        map.mset(unused, 2, 0, 0);
+        map.mset(unused, 0, 1, 17);
+        map.mset(unused, 1, 1, 18);
+
+        map
+    }
+
+    #[test]
+    fn map_draws_exactly_celw_by_celh_cells() {
+        let sprite_sheet = block_sprite_sheet();
+        let map = block_map();
+        let mut draw_data = DrawData::new();
+
+        draw_data.map(map_options(1, 1, 0), &map, &Flags::new(), &sprite_sheet);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 7, 9, 2),
+            ["811111110", "000000000"]
+        );
+    }
+
+    #[test]
+    fn map_skips_sprite_zero() {
+        let mut sprite_sheet = block_sprite_sheet();
+        for x in 0..8 {
+            sprite_sheet.set(x, 0, colors::WHITE);
+        }
+        let map = block_map();
+        let mut draw_data = DrawData::new();
+
+        draw_data.map(map_options(3, 1, 0), &map, &Flags::new(), &sprite_sheet);
+
+        assert_eq!(screen_region(&draw_data, 15, 0, 10, 1), ["2000000000"]);
+    }
+
+    #[test]
+    fn map_layer_draws_sprites_with_every_flag_of_the_layer() {
+        let sprite_sheet = block_sprite_sheet();
+        let map = block_map();
+        let mut flags = Flags::new();
+        flags.fset_all(1, 0b101);
+        flags.fset_all(2, 0b100);
+        flags.fset_all(17, 0b111);
+        let mut draw_data = DrawData::new();
+
+        draw_data.map(map_options(2, 2, 0b101), &map, &flags, &sprite_sheet);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 16, 1), ["8111111100000000"]);
+        assert_eq!(screen_region(&draw_data, 0, 8, 16, 1), ["3333333300000000"]);
+
+        // Layer 0 draws every sprite, regardless of its flags.
+        draw_data.map(map_options(2, 2, 0), &map, &flags, &sprite_sheet);
+        assert_eq!(screen_region(&draw_data, 0, 8, 16, 1), ["3333333344444444"]);
+    }
+
+    #[test]
+    fn map_draws_nothing_outside_the_map() {
+        let sprite_sheet = block_sprite_sheet();
+        let map = block_map();
+        let mut draw_data = DrawData::new();
+        let options = MapOptions {
+            cell_x: -1,
+            cell_y: -1,
+            screen_x: 3,
+            screen_y: 2,
+            ..map_options(2, 2, 0)
+        };
+
+        draw_data.map(options, &map, &Flags::new(), &sprite_sheet);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 9, 20, 2),
+            ["00000000000000000000", "00000000000811111110"]
+        );
+    }
+
+    #[test]
+    fn print_returns_the_right_most_x() {
+        let mut draw_data = DrawData::new();
+
+        assert_eq!(draw_data.print("hello", 10, 0, colors::WHITE), 30);
+        assert_eq!(draw_data.print("ab\nlonger", 10, 0, colors::WHITE), 34);
+        assert_eq!(draw_data.print("", 10, 0, colors::WHITE), 10);
+    }
+
+    #[test]
+    fn print_handles_newlines_and_tabs() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.print("i\n\ti", 1, 0, colors::WHITE);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 21, 12),
+            [
+                "077700000000000000000",
+                "007000000000000000000",
+                "007000000000000000000",
+                "007000000000000000000",
+                "077700000000000000000",
+                "000000000000000000000",
+                "000000000000000007770",
+                "000000000000000000700",
+                "000000000000000000700",
+                "000000000000000000700",
+                "000000000000000007770",
+                "000000000000000000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn print_draws_uppercase_letters_with_the_puny_font() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.print("a", 0, 0, colors::WHITE);
+        draw_data.print("A", 4, 0, colors::WHITE);
+        draw_data.print("|", 8, 0, colors::WHITE);
+        let right_most = draw_data.print("\x10é~", 0, 6, colors::WHITE);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 12, 5),
+            [
+                "777000000700",
+                "707077700700",
+                "777070700700",
+                "707077700700",
+                "707070700700",
+            ]
+        );
+        assert_eq!(right_most, 8);
+        assert_eq!(screen_region(&draw_data, 0, 6, 4, 1), ["7777"]);
+    }
+
+    #[test]
+    fn print_control_codes_change_colors() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.print("\x0c8i\x02ci", 1, 1, colors::WHITE);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 10, 7),
+            [
+                "0000ccccc0",
+                "0888c888c0",
+                "0080cc8cc0",
+                "0080cc8cc0",
+                "0080cc8cc0",
+                "0888c888c0",
+                "0000ccccc0",
+            ]
+        );
+    }
+
+    #[test]
+    fn print_control_codes_move_the_cursor() {
+        let mut draw_data = DrawData::new();
+
+        let right_most = draw_data.print("i\x03ki\x04ki\x08\x08\x08i", 0, 0, colors::WHITE);
+
+        assert_eq!(right_most, 16);
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 16, 2),
+            ["7770000077700000", "0700000007000000"]
+        );
+        assert_eq!(
+            screen_region(&draw_data, 0, 7, 16, 2),
+            ["0000070000000700", "0000777000007770"]
+        );
+    }
+
+    #[test]
+    fn print_at_cursor_continues_below_the_last_print() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.print("i\ni", 2, 3, colors::RED);
+        let previous = draw_data.cursor(2, 15);
+        draw_data.print_at_cursor("i");
+        draw_data.print_at_cursor("i");
+
+        assert_eq!(previous, (2, 15));
+        assert_eq!(screen_region(&draw_data, 2, 15, 3, 1), ["888"]);
+        assert_eq!(screen_region(&draw_data, 2, 21, 3, 1), ["888"]);
+        assert_eq!(draw_data.cursor(0, 0), (2, 27));
+    }
+
+    #[test]
+    fn palt_mask_makes_several_colors_transparent() {
+        let sprite_sheet = block_sprite_sheet();
+        let mut draw_data = DrawData::new();
+        draw_data.rectfill(0, 0, 127, 127, colors::WHITE);
+
+        let previous = draw_data.palt_mask(0x2080);
+        draw_data.spr_(&sprite_sheet, 1, 0, 0, 2.0, 1.0, false, false);
+
+        assert_eq!(previous, 0x8000);
+        assert_eq!(screen_region(&draw_data, 0, 0, 16, 1), ["7111111197777777"]);
+
+        draw_data.palt_(colors::RED, false);
+        draw_data.palt_(colors::DARK_BLUE, true);
+        draw_data.spr_(&sprite_sheet, 1, 0, 8, 2.0, 1.0, false, false);
+        assert_eq!(screen_region(&draw_data, 0, 8, 16, 1), ["8777777797777777"]);
+    }
+
+    #[test]
+    fn screen_palette_only_changes_the_displayed_colors() {
+        let mut draw_data = DrawData::new();
+        draw_data.pset(0, 0, colors::RED);
+        draw_data.pal_(colors::RED, colors::BLUE, 1);
+        draw_data.pset(1, 0, colors::RED);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 3, 1), ["880"]);
+        assert_eq!(draw_data.buffer()[0], 0x88);
+        assert_eq!(
+            draw_data.screen_palette()[colors::RED as usize],
+            colors::BLUE
+        );
+    }
+
+    #[test]
+    fn reset_pal_resets_every_palette_and_transparency() {
+        let mut draw_data = DrawData::new();
+        draw_data.pal_(colors::RED, colors::BLUE, 0);
+        draw_data.pal_(colors::RED, colors::GREEN, 1);
+        draw_data.palt_mask(0xffff);
+
+        draw_data.reset_pal();
+        draw_data.rectfill(0, 0, 1, 0, colors::RED);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 2, 1), ["88"]);
+        assert_eq!(draw_data.screen_palette(), &ORIGINAL_PALETTE);
+        assert_eq!(draw_data.palt_mask(0), 0x8000);
+    }
+
+    #[test]
+    fn screen_palette_can_show_extended_colors() {
+        let mut draw_data = DrawData::new();
+        draw_data.pset(0, 0, colors::RED);
+        draw_data.pal_(colors::RED, 136, 1);
+        draw_data.pal_(colors::BLACK, 128 + 16, 1);
+
+        let screen_palette = draw_data.screen_palette();
+
+        assert_eq!(draw_data.pget(0, 0), colors::RED);
+        assert_eq!(
+            colors::rgb(screen_palette[colors::RED as usize]),
+            [0xBE, 0x12, 0x50]
+        );
+        assert_eq!(
+            colors::rgb(screen_palette[colors::BLACK as usize]),
+            [0x29, 0x18, 0x14]
+        );
+    }
+
+    #[test]
+    fn screen_palette_areas_limit_the_screen_palette() {
+        let mut draw_data = DrawData::new();
+        draw_data.pal_(colors::RED, 136, 1);
+
+        assert_eq!(
+            draw_data.screen_palette_at(100, 100)[colors::RED as usize],
+            136
+        );
+
+        draw_data.screen_palette_areas(&[(8, 8, 16, 16), (64, 0, 1, 1)]);
+
+        assert_eq!(draw_data.screen_palette_at(8, 8)[colors::RED as usize], 136);
+        assert_eq!(
+            draw_data.screen_palette_at(23, 23)[colors::RED as usize],
+            136
+        );
+        assert_eq!(
+            draw_data.screen_palette_at(64, 0)[colors::RED as usize],
+            136
+        );
+        assert_eq!(draw_data.screen_palette_at(24, 8), &ORIGINAL_PALETTE);
+        assert_eq!(draw_data.screen_palette_at(100, 100), &ORIGINAL_PALETTE);
+
+        draw_data.screen_palette_areas(&[]);
+
+        assert_eq!(
+            draw_data.screen_palette_at(100, 100)[colors::RED as usize],
+            136
+        );
+    }
+
+    #[test]
+    fn buffer_packs_two_pixels_per_byte() {
+        let mut draw_data = DrawData::new();
+        draw_data.pset(0, 0, colors::RED);
+        draw_data.pset(1, 0, colors::BLUE);
+        draw_data.pset(127, 127, colors::WHITE);
+
+        assert_eq!(draw_data.buffer()[0], 0xc8);
+        assert_eq!(draw_data.buffer()[WIDTH * WIDTH / 2 - 1], 0x70);
+        assert_eq!(draw_data.pget(1, 0), colors::BLUE);
+    }
+
+    #[test]
+    fn pget_returns_the_drawn_index_even_if_colors_look_the_same() {
+        let mut draw_data = DrawData::new();
+        draw_data.pal_(colors::BLUE, colors::RED, 1);
+        draw_data.pset(0, 0, colors::RED);
+        draw_data.pset(1, 0, colors::BLUE);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 2, 1), ["8c"]);
+    }
+
+    /// Map with sprite 1 at cell (0, 0) and sprite 2 at (1, 0). Sprite 1's pixel (x, y) is
+    /// color `x + y`, sprite 2 is red. Sprite 2 has flag 0 set.
+    fn tline_resources() -> (Map, Flags, SpriteSheet) {
+        let mut sprite_sheet = SpriteSheet::new();
+        for x in 0..8 {
+            for y in 0..8 {
+                sprite_sheet.set(8 + x, y, (x + y) as u8);
+                sprite_sheet.set(16 + x, y, colors::RED);
+            }
+        }
+        let mut map = Map::new();
+        map.mset(&mut sprite_sheet, 0, 0, 1);
+        map.mset(&mut sprite_sheet, 1, 0, 2);
+        let mut flags = Flags::new();
+        flags.fset(2, 0, true);
+
+        (map, flags, sprite_sheet)
+    }
+
+    fn tline(draw_data: &mut DrawData, y: i32, mx: f32, my: f32, mdx: f32, layers: u8) {
+        let (map, flags, sprite_sheet) = tline_resources();
+        let [mx, my, mdx] = [mx, my, mdx].map(Fixed::from);
+
+        draw_data.tline(
+            0,
+            y,
+            15,
+            y,
+            mx,
+            my,
+            mdx,
+            Fixed::ZERO,
+            layers,
+            &map,
+            &flags,
+            &sprite_sheet,
+        );
+    }
+
+    #[test]
+    fn tline_samples_the_map_pixel_by_pixel() {
+        let mut draw_data = DrawData::new();
+        tline(&mut draw_data, 0, 0.0, 0.0, 0.125, 0);
+        tline(&mut draw_data, 1, 0.0, 0.5, 0.125, 0);
+        tline(&mut draw_data, 2, 0.5, 0.0, 0.0625, 0);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 16, 3),
+            ["0123456788888888", "456789ab88888888", "4455667788888888"]
+        );
+    }
+
+    #[test]
+    fn tline_honors_palette_transparency_and_camera() {
+        let mut draw_data = DrawData::new();
+        draw_data.rectfill(0, 0, 127, 127, colors::WHITE);
+        draw_data.pal(colors::RED, colors::BLUE);
+        draw_data.palt_(1, true);
+        draw_data.camera(-2, -3);
+        tline(&mut draw_data, 0, 0.0, 0.0, 0.125, 0);
+
+        draw_data.camera(0, 0);
+        // Colors 0 and 1 are transparent, red is drawn as blue.
+        assert_eq!(
+            screen_region(&draw_data, 0, 3, 18, 1),
+            ["7777234567cccccccc"]
+        );
+    }
+
+    #[test]
+    fn tline_wraps_inside_the_configured_region() {
+        let mut draw_data = DrawData::new();
+        draw_data.poke_register(0x38, 1);
+        tline(&mut draw_data, 0, 0.0, 0.0, 0.125, 0);
+
+        draw_data.poke_register(0x3a, 1);
+        tline(&mut draw_data, 1, 0.0, 0.0, 0.125, 0);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 16, 2),
+            ["0123456701234567", "8888888888888888"]
+        );
+    }
+
+    #[test]
+    fn tline_only_draws_sprites_in_layers() {
+        let mut draw_data = DrawData::new();
+        tline(&mut draw_data, 0, 0.0, 0.0, 0.125, 1);
+
+        assert_eq!(screen_region(&draw_data, 0, 0, 16, 1), ["0000000088888888"]);
+    }
+
+    /// Points of the ideal ellipse that fits in the pixel centers of a `w` by `h` rectangle.
+    fn ideal_oval(w: i32, h: i32) -> impl Iterator<Item = (f32, f32)> {
+        let (a, b) = (w as f32 / 2.0, h as f32 / 2.0);
+
+        (0..360).map(move |step| {
+            let angle = step as f32 * std::f32::consts::TAU / 360.0;
+
+            (a + a * angle.cos(), b + b * angle.sin())
+        })
+    }
+
+    fn distance((x0, y0): (f32, f32), (x1, y1): (i32, i32)) -> f32 {
+        (x0 - x1 as f32).hypot(y0 - y1 as f32)
+    }
+
+    // The ellipse isn't checked against pico8's output, so ovals are compared with the ideal
+    // one: every pixel is near it, and it has no gaps.
+    #[test]
+    fn ovals_follow_the_ideal_ellipse() {
+        for w in 0..16 {
+            for h in 0..16 {
+                let mut draw_data = DrawData::new();
+                draw_data.oval(0, 0, w, h, colors::RED);
+                let pixels = drawn_pixels(&draw_data);
+
+                for &pixel in &pixels {
+                    let closest = ideal_oval(w, h)
+                        .map(|point| distance(point, pixel))
+                        .fold(f32::MAX, f32::min);
+                    assert!(closest < 0.75, "{w}x{h}: {pixel:?} is {closest} away");
+                }
+                for point in ideal_oval(w, h) {
+                    let closest = pixels
+                        .iter()
+                        .map(|&pixel| distance(point, pixel))
+                        .fold(f32::MAX, f32::min);
+                    assert!(closest < 1.0, "{w}x{h}: gap of {closest} at {point:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ovalfill_fills_the_rows_of_the_oval() {
+        for w in 0..16 {
+            for h in 0..16 {
+                let mut outline = DrawData::new();
+                outline.oval(0, 0, w, h, colors::RED);
+                let mut filled = DrawData::new();
+                filled.ovalfill(w, h, 0, 0, colors::RED);
+
+                for y in 0..=h {
+                    let row: Vec<i32> = drawn_pixels(&outline)
+                        .into_iter()
+                        .filter(|pixel| pixel.1 == y)
+                        .map(|pixel| pixel.0)
+                        .collect();
+                    let (left, right) = (row[0], row[row.len() - 1]);
+
+                    for x in 0..=w {
+                        let inside = (left..=right).contains(&x);
+                        assert_eq!(
+                            filled.pget(x, y) == colors::RED,
+                            inside,
+                            "{w}x{h}: ({x}, {y})"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn thin_ovals_reach_their_tips() {
+        let mut draw_data = DrawData::new();
+        draw_data.oval(0, 0, 2, 7, colors::RED);
+        draw_data.oval(4, 0, 4, 5, colors::RED);
+        draw_data.oval(6, 0, 9, 0, colors::RED);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 10, 8),
+            [
+                "0800808888",
+                "0800800000",
+                "8080800000",
+                "8080800000",
+                "8080800000",
+                "8080800000",
+                "0800000000",
+                "0800000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn ovals_are_symmetric_and_touch_every_side() {
+        for w in 0..16 {
+            for h in 0..16 {
+                let mut draw_data = DrawData::new();
+                draw_data.oval(0, 0, w, h, colors::RED);
+                let pixels = drawn_pixels(&draw_data);
+
+                for &(x, y) in &pixels {
+                    assert!(x <= w && y <= h, "{w}x{h}: ({x}, {y})");
+                    assert!(pixels.contains(&(w - x, y)), "{w}x{h}: ({x}, {y})");
+                    assert!(pixels.contains(&(x, h - y)), "{w}x{h}: ({x}, {y})");
+                }
+                assert!((0..=w).all(|x| pixels.iter().any(|pixel| pixel.0 == x)));
+                assert!((0..=h).all(|y| pixels.iter().any(|pixel| pixel.1 == y)));
+            }
+        }
+    }
+
+    #[test]
+    fn rrect_rounds_the_corners() {
+        let mut draw_data = DrawData::new();
+        draw_data.rrect(0, 0, 10, 7, 3, colors::RED);
+        draw_data.rrectfill(11, 0, 10, 7, 2, colors::RED);
+
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 21, 7),
+            [
+                "008888880000888888880",
+                "080000008008888888888",
+                "800000000808888888888",
+                "800000000808888888888",
+                "800000000808888888888",
+                "080000008008888888888",
+                "008888880000888888880",
+            ]
+        );
+    }
+
+    #[test]
+    fn rrect_radius_is_limited_by_the_shortest_side() {
+        let mut rrect = DrawData::new();
+        rrect.rrect(0, 0, 10, 3, 100, colors::RED);
+        let mut rect = DrawData::new();
+        rect.rrect(0, 0, 10, 3, 0, colors::RED);
+        rect.rect(0, 5, 9, 7, colors::RED);
+
+        assert_eq!(
+            screen_region(&rrect, 0, 0, 10, 3),
+            ["0888888880", "8000000008", "0888888880"]
+        );
+        assert_eq!(
+            screen_region(&rect, 0, 0, 10, 3),
+            screen_region(&rect, 0, 5, 10, 3)
+        );
+
+        rrect.rrectfill(0, 0, 0, 5, 1, colors::BLUE);
+        assert!(!drawn_pixels(&rrect).contains(&(0, 4)), "empty rectangle");
+    }
+
+    #[test]
+    fn line_to_continues_from_the_last_line() {
+        let mut draw_data = DrawData::new();
+        assert_eq!(draw_data.color(colors::RED), 6);
+
+        draw_data.line_to(0, 0);
+        assert!(drawn_pixels(&draw_data).is_empty(), "only moves the end");
+
+        draw_data.line_to(3, 0);
+        draw_data.line_to(3, 2);
+        assert_eq!(
+            screen_region(&draw_data, 0, 0, 4, 3),
+            ["8888", "0008", "0008"]
+        );
+        assert_eq!(draw_data.peek_register(0x3c), Some(3));
+        assert_eq!(draw_data.peek_register(0x3e), Some(2));
+
+        draw_data.set_line_end(None);
+        assert_eq!(draw_data.peek_register(0x35), Some(1));
+        draw_data.line_to(0, 2);
+        assert_eq!(draw_data.pget(1, 2), colors::BLACK);
+    }
+
+    #[test]
+    fn line_end_pokes_are_kept_while_it_is_invalid() {
+        let mut draw_data = DrawData::new();
+        draw_data.set_line_end(None);
+
+        draw_data.poke_register(0x3c, 5);
+        draw_data.poke_register(0x3e, 1);
+        assert_eq!(draw_data.peek_register(0x3c), Some(5));
+        assert_eq!(draw_data.peek_register(0x35), Some(1));
+
+        draw_data.poke_register(0x35, 0);
+        draw_data.color(colors::RED);
+        draw_data.line_to(5, 3);
+        assert_eq!(draw_data.pget(5, 1), colors::RED);
+        assert_eq!(draw_data.pget(5, 0), colors::BLACK);
+    }
 }
@@ -0,0 +1,527 @@
+//! Pico8's numbers: 16.16 fixed point, see <https://pico-8.fandom.com/wiki/Math>.
+use std::f64::consts::TAU;
+use std::fmt::{self, Display};
+use std::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Neg, Not, Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
+};
+
+/// A pico8 number: a signed 16.16 fixed point value.
+///
+/// Arithmetic wraps around like in pico8 (`32767 + 1 == -32768`), and division by zero
+/// doesn't panic, it returns the largest (or smallest) representable number.
+///
+/// ```
+/// # use runty8_core::Fixed;
+/// let x = Fixed::from(1) / Fixed::from(3);
+///
+/// assert_eq!(x.to_string(), "0.3333");
+/// assert_eq!(Fixed::from(32767) + Fixed::from(1), Fixed::from(-32768));
+/// assert_eq!(Fixed::from(1) / Fixed::ZERO, Fixed::MAX);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    /// Number of bits after the point.
+    pub const FRACTION_BITS: u32 = 16;
+    /// `0`, also what [`Fixed::default`] returns.
+    pub const ZERO: Self = Self(0);
+    /// `1`, whose bits are `0x0001.0000`.
+    pub const ONE: Self = Self(1 << Self::FRACTION_BITS);
+    /// `0x7fff.ffff`, roughly 32767.99998.
+    pub const MAX: Self = Self(i32::MAX);
+    /// `-0x8000`.
+    pub const MIN: Self = Self(i32::MIN);
+
+    /// Number whose 16.16 representation is `bits`, like pico8's `0x1234.5678` literals.
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    /// 16.16 representation of the number, what `peek4` and `poke4` work with.
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Flr>
+    pub const fn flr(self) -> Self {
+        Self(self.0 & !0xffff)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Ceil>
+    pub fn ceil(self) -> Self {
+        -(-self).flr()
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Abs>
+    ///
+    /// Like pico8, `abs(-32768)` is `0x7fff.ffff`.
+    pub const fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Sgn>
+    ///
+    /// 1 for positive numbers and zero, -1 for negative ones.
+    pub const fn sgn(self) -> Self {
+        if self.0 < 0 {
+            Self(-Self::ONE.0)
+        } else {
+            Self::ONE
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Sqrt>
+    ///
+    /// Square root, rounded down. Negative numbers give 0.
+    pub fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Self::ZERO;
+        }
+
+        let squared = (self.0 as u64) << Self::FRACTION_BITS;
+        // Start above the root, Newton's method converges down to its floor.
+        let mut root = 1 << ((64 - squared.leading_zeros()) / 2 + 1);
+        loop {
+            let next = (root + squared / root) / 2;
+            if next >= root {
+                return Self(root as i32);
+            }
+            root = next;
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Sin>
+    ///
+    /// Takes turns (1 is a full circle) and is inverted, to match screen space where y grows
+    /// downwards: `sin(0.25) == -1`.
+    pub fn sin(self) -> Self {
+        Self::from_f64(-self.turns().sin())
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Cos>
+    ///
+    /// Takes turns (1 is a full circle): `cos(0.5) == -1`.
+    pub fn cos(self) -> Self {
+        Self::from_f64(self.turns().cos())
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Atan2>
+    ///
+    /// Angle of the vector `(dx, dy)`, in turns from 0 to 1. It's inverted like [`Fixed::sin`],
+    /// so `atan2(0, -1) == 0.25` (pointing up on the screen). `atan2(0, 0)` is 0.25.
+    pub fn atan2(dx: Self, dy: Self) -> Self {
+        if dx == Self::ZERO && dy == Self::ZERO {
+            return Self::from_bits(0x4000);
+        }
+
+        let turns = (-dy.to_f64()).atan2(dx.to_f64()) / TAU;
+        let turns = Self::from_f64(turns);
+
+        // `from_f64` can round 0.99999... up to 1.
+        Self(turns.0 & 0xffff)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Lshr>
+    ///
+    /// Logical shift right: unlike `>>`, the sign bit isn't copied.
+    pub fn lshr(self, n: i32) -> Self {
+        match n {
+            ..=-1 => self << -n,
+            0..=31 => Self(((self.0 as u32) >> n) as i32),
+            _ => Self::ZERO,
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rotl>
+    pub fn rotl(self, n: i32) -> Self {
+        Self(self.0.rotate_left(n.rem_euclid(32) as u32))
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rotr>
+    pub fn rotr(self, n: i32) -> Self {
+        Self(self.0.rotate_right(n.rem_euclid(32) as u32))
+    }
+
+    /// Angle in radians, only the fractional part of the number matters.
+    fn turns(self) -> f64 {
+        (self.0 & 0xffff) as f64 / 65536.0 * TAU
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / 65536.0
+    }
+
+    /// Nearest number to `value`, wrapping around if it's out of range.
+    fn from_f64(value: f64) -> Self {
+        Self((value * 65536.0).round() as i64 as i32)
+    }
+}
+
+impl Display for Fixed {
+    /// Formats the number like pico8's `print` and `tostr`: up to 4 decimals, without
+    /// trailing zeros.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let negative = self.0 < 0;
+        let magnitude = (self.0 as i64).abs();
+        let integer = magnitude >> Self::FRACTION_BITS;
+        // Rounds the fraction to 4 decimals, 0.99995 and above don't carry into the integer.
+        let fraction = (((magnitude & 0xffff) * 10000 + 0x8000) >> Self::FRACTION_BITS).min(9999);
+
+        if negative && (integer != 0 || fraction != 0) {
+            f.write_str("-")?;
+        }
+        write!(f, "{integer}")?;
+
+        if fraction != 0 {
+            let decimals = format!("{fraction:04}");
+            write!(f, ".{}", decimals.trim_end_matches('0'))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{self} (0x{:04x}.{:04x})",
+            (self.0 >> 16) as u16,
+            self.0 as u16
+        )
+    }
+}
+
+impl From<i32> for Fixed {
+    /// Only the lowest 16 bits are kept, like pico8's integer overflow.
+    fn from(n: i32) -> Self {
+        Self(n << Self::FRACTION_BITS)
+    }
+}
+
+impl From<i16> for Fixed {
+    fn from(n: i16) -> Self {
+        Self::from(n as i32)
+    }
+}
+
+impl From<u8> for Fixed {
+    fn from(n: u8) -> Self {
+        Self::from(n as i32)
+    }
+}
+
+impl From<f32> for Fixed {
+    /// Nearest representable number, wrapping around if it's out of range.
+    fn from(n: f32) -> Self {
+        Self::from_f64(n as f64)
+    }
+}
+
+impl From<f64> for Fixed {
+    /// Nearest representable number, wrapping around if it's out of range.
+    fn from(n: f64) -> Self {
+        Self::from_f64(n)
+    }
+}
+
+impl From<Fixed> for i32 {
+    /// Rounds down, like pico8 does with coordinates and other integer arguments.
+    fn from(n: Fixed) -> Self {
+        n.0 >> Fixed::FRACTION_BITS
+    }
+}
+
+impl From<Fixed> for f32 {
+    fn from(n: Fixed) -> Self {
+        n.to_f64() as f32
+    }
+}
+
+impl From<Fixed> for f64 {
+    fn from(n: Fixed) -> Self {
+        n.to_f64()
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(((self.0 as i64 * rhs.0 as i64) >> Self::FRACTION_BITS) as i32)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    /// Dividing by zero, or overflowing, gives `0x7fff.ffff` or `-0x7fff.ffff` depending
+    /// on the signs of the operands.
+    fn div(self, rhs: Self) -> Self {
+        if rhs.0 != 0 {
+            let quotient = ((self.0 as i64) << Self::FRACTION_BITS) / rhs.0 as i64;
+
+            if let Ok(quotient) = i32::try_from(quotient) {
+                return Self(quotient);
+            }
+        }
+
+        if (self.0 ^ rhs.0) >= 0 {
+            Self::MAX
+        } else {
+            Self(-i32::MAX)
+        }
+    }
+}
+
+impl Rem for Fixed {
+    type Output = Self;
+
+    /// Like pico8's `%`, the result has the sign of the divisor's absolute value (it's never
+    /// negative). `x % 0` is 0.
+    fn rem(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            Self::ZERO
+        } else {
+            Self((self.0 as i64).rem_euclid((rhs.0 as i64).abs()) as i32)
+        }
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+impl Shl<i32> for Fixed {
+    type Output = Self;
+
+    /// Negative amounts shift right instead.
+    fn shl(self, n: i32) -> Self {
+        match n {
+            ..=-1 => self >> -n,
+            0..=31 => Self(self.0 << n),
+            _ => Self::ZERO,
+        }
+    }
+}
+
+impl Shr<i32> for Fixed {
+    type Output = Self;
+
+    /// Arithmetic shift (the sign bit is copied). Negative amounts shift left instead.
+    fn shr(self, n: i32) -> Self {
+        match n {
+            ..=-1 => self << -n,
+            0..=31 => Self(self.0 >> n),
+            _ => Self(self.0 >> 31),
+        }
+    }
+}
+
+impl Not for Fixed {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl $trait for Fixed {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                Self(self.0 $op rhs.0)
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, &);
+impl_bit_op!(BitOr, bitor, |);
+impl_bit_op!(BitXor, bitxor, ^);
+
+macro_rules! impl_assign_op {
+    ($trait:ident, $method:ident, $op:tt, $rhs:ty) => {
+        impl $trait<$rhs> for Fixed {
+            fn $method(&mut self, rhs: $rhs) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+impl_assign_op!(AddAssign, add_assign, +, Fixed);
+impl_assign_op!(SubAssign, sub_assign, -, Fixed);
+impl_assign_op!(MulAssign, mul_assign, *, Fixed);
+impl_assign_op!(DivAssign, div_assign, /, Fixed);
+impl_assign_op!(RemAssign, rem_assign, %, Fixed);
+impl_assign_op!(BitAndAssign, bitand_assign, &, Fixed);
+impl_assign_op!(BitOrAssign, bitor_assign, |, Fixed);
+impl_assign_op!(BitXorAssign, bitxor_assign, ^, Fixed);
+impl_assign_op!(ShlAssign, shl_assign, <<, i32);
+impl_assign_op!(ShrAssign, shr_assign, >>, i32);
+
+#[cfg(test)]
+mod tests {
+    use super::Fixed;
+
+    fn fixed(bits: u32) -> Fixed {
+        Fixed::from_bits(bits as i32)
+    }
+
+    fn n(value: f64) -> Fixed {
+        Fixed::from(value)
+    }
+
+    #[test]
+    fn arithmetic_wraps_around() {
+        assert_eq!(n(32767.0) + n(1.0), n(-32768.0));
+        assert_eq!(n(-32768.0) - n(1.0), n(32767.0));
+        assert_eq!(-n(-32768.0), n(-32768.0));
+        assert_eq!(n(200.0) * n(200.0), n(-25536.0));
+        assert_eq!(Fixed::from(40000), n(-25536.0));
+    }
+
+    #[test]
+    fn multiplication_rounds_down() {
+        assert_eq!(n(1.5) * n(2.5), n(3.75));
+        assert_eq!(fixed(0x0000_0001) * n(0.5), Fixed::ZERO);
+        assert_eq!(fixed(0xffff_ffff) * n(0.5), fixed(0xffff_ffff));
+    }
+
+    #[test]
+    fn division() {
+        assert_eq!(n(1.0) / n(3.0), fixed(0x0000_5555));
+        assert_eq!(n(-1.0) / n(3.0), fixed(0xffff_aaab));
+        assert_eq!(n(1.0) / Fixed::ZERO, fixed(0x7fff_ffff));
+        assert_eq!(n(-1.0) / Fixed::ZERO, fixed(0x8000_0001));
+        assert_eq!(n(0.0) / Fixed::ZERO, fixed(0x7fff_ffff));
+        assert_eq!(n(-32768.0) / n(1.0), n(-32768.0));
+        assert_eq!(n(16384.0) / n(0.25), fixed(0x7fff_ffff));
+    }
+
+    #[test]
+    fn remainder_is_never_negative() {
+        assert_eq!(n(7.0) % n(3.0), n(1.0));
+        assert_eq!(n(-7.0) % n(3.0), n(2.0));
+        assert_eq!(n(-7.0) % n(-3.0), n(2.0));
+        assert_eq!(n(5.5) % n(2.0), n(1.5));
+        assert_eq!(n(5.0) % Fixed::ZERO, Fixed::ZERO);
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(n(1.0) << 4, n(16.0));
+        assert_eq!(n(1.0) >> 1, n(0.5));
+        assert_eq!(n(-1.0) >> 1, n(-0.5));
+        assert_eq!(n(1.0) << -1, n(0.5));
+        assert_eq!(n(1.0) << 32, Fixed::ZERO);
+        assert_eq!(n(-1.0) >> 40, fixed(0xffff_ffff));
+        assert_eq!(n(-1.0).lshr(1), fixed(0x7fff_8000));
+        assert_eq!(n(-1.0).lshr(40), Fixed::ZERO);
+        assert_eq!(fixed(0x8000_0001).rotl(1), fixed(0x0000_0003));
+        assert_eq!(fixed(0x8000_0001).rotr(1), fixed(0xc000_0000));
+        assert_eq!(fixed(0x8000_0001).rotr(-1), fixed(0x0000_0003));
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_eq!(n(5.5) & n(3.0), n(1.0));
+        assert_eq!(n(4.0) | n(0.5), n(4.5));
+        assert_eq!(n(6.0) ^ n(3.0), n(5.0));
+        assert_eq!(!Fixed::ZERO, fixed(0xffff_ffff));
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(n(5.9).flr(), n(5.0));
+        assert_eq!(n(-5.2).flr(), n(-6.0));
+        assert_eq!(n(5.2).ceil(), n(6.0));
+        assert_eq!(n(-5.9).ceil(), n(-5.0));
+        assert_eq!(n(7.0).ceil(), n(7.0));
+        assert_eq!(i32::from(n(-0.5)), -1);
+    }
+
+    #[test]
+    fn abs_and_sgn() {
+        assert_eq!(n(-3.5).abs(), n(3.5));
+        assert_eq!(n(-32768.0).abs(), Fixed::MAX);
+        assert_eq!(n(-0.1).sgn(), n(-1.0));
+        assert_eq!(Fixed::ZERO.sgn(), n(1.0));
+    }
+
+    #[test]
+    fn sqrt() {
+        assert_eq!(n(4.0).sqrt(), n(2.0));
+        assert_eq!(n(2.0).sqrt(), fixed(0x0001_6a09));
+        assert_eq!(n(0.25).sqrt(), n(0.5));
+        assert_eq!(Fixed::MAX.sqrt(), fixed(0x00b5_04f3));
+        assert_eq!(n(-4.0).sqrt(), Fixed::ZERO);
+    }
+
+    #[test]
+    fn trigonometry() {
+        assert_eq!(n(0.0).sin(), n(0.0));
+        assert_eq!(n(0.25).sin(), n(-1.0));
+        assert_eq!(n(0.75).sin(), n(1.0));
+        assert_eq!(n(1.25).sin(), n(-1.0));
+        assert_eq!(n(0.125).sin(), fixed(0xffff_4afb));
+        assert_eq!(n(0.0).cos(), n(1.0));
+        assert_eq!(n(0.5).cos(), n(-1.0));
+
+        assert_eq!(Fixed::atan2(n(1.0), n(0.0)), n(0.0));
+        assert_eq!(Fixed::atan2(n(0.0), n(-1.0)), n(0.25));
+        assert_eq!(Fixed::atan2(n(-1.0), n(0.0)), n(0.5));
+        assert_eq!(Fixed::atan2(n(0.0), n(1.0)), n(0.75));
+        assert_eq!(Fixed::atan2(n(1.0), n(-1.0)), n(0.125));
+        assert_eq!(Fixed::atan2(n(0.0), n(0.0)), n(0.25));
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(n(0.0).to_string(), "0");
+        assert_eq!(n(-12.0).to_string(), "-12");
+        assert_eq!(n(0.5).to_string(), "0.5");
+        assert_eq!(n(-0.25).to_string(), "-0.25");
+        assert_eq!((n(1.0) / n(3.0)).to_string(), "0.3333");
+        assert_eq!((n(2.0) / n(3.0)).to_string(), "0.6667");
+        assert_eq!(fixed(0x0000_0001).to_string(), "0");
+        assert_eq!(fixed(0xffff_ffff).to_string(), "0");
+        assert_eq!(Fixed::MAX.to_string(), "32767.9999");
+        assert_eq!(Fixed::MIN.to_string(), "-32768");
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Fixed::from(3_u8), n(3.0));
+        assert_eq!(Fixed::from(-3_i16), n(-3.0));
+        assert_eq!(Fixed::from(0.1_f32), fixed(0x0000_199a));
+        assert_eq!(f32::from(n(-2.5)), -2.5);
+        assert_eq!(i32::from(n(2.75)), 2);
+        assert_eq!(Fixed::from_bits(0x0001_8000).to_bits(), 0x0001_8000);
+    }
+}
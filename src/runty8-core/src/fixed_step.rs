@@ -0,0 +1,67 @@
+/// Splits the real time passing into frames of a fixed length, see [`App::FPS`](crate::App::FPS).
+#[derive(Clone, Debug)]
+pub struct FixedStep {
+    fps: u32,
+    elapsed_millis: f64,
+    frames_run: u64,
+}
+
+impl FixedStep {
+    /// Steps of `1000 / fps` milliseconds.
+    pub fn new(fps: u32) -> Self {
+        Self {
+            fps,
+            elapsed_millis: 0.0,
+            frames_run: 0,
+        }
+    }
+
+    /// Length of a frame, in milliseconds.
+    pub fn step_millis(&self) -> f64 {
+        1000.0 / self.fps as f64
+    }
+
+    /// Registers `delta_millis` of real time passing, returns how many frames should run
+    /// to catch up with it.
+    pub fn advance(&mut self, delta_millis: f64) -> u32 {
+        // Frames are counted from the total time instead of subtracting each step from
+        // an accumulator, so rounding errors don't pile up and drop frames.
+        const EPSILON: f64 = 1e-6;
+
+        self.elapsed_millis += delta_millis;
+        let frames_due = (self.elapsed_millis * self.fps as f64 / 1000.0 + EPSILON).floor() as u64;
+        let frames = frames_due.saturating_sub(self.frames_run);
+        self.frames_run += frames;
+
+        frames as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedStep;
+
+    /// Frames run during a second of ticks of `tick_millis`.
+    fn frames_in_a_second(fps: u32, tick_millis: f64) -> u32 {
+        let mut fixed_step = FixedStep::new(fps);
+        let ticks = (1000.0 / tick_millis).round() as u32;
+
+        (0..ticks).map(|_| fixed_step.advance(tick_millis)).sum()
+    }
+
+    #[test]
+    fn runs_fps_frames_per_second() {
+        for tick_millis in [1.0, 1000.0 / 144.0, 1000.0 / 60.0, 1000.0 / 30.0, 100.0] {
+            assert_eq!(frames_in_a_second(30, tick_millis), 30, "{tick_millis}");
+            assert_eq!(frames_in_a_second(60, tick_millis), 60, "{tick_millis}");
+        }
+    }
+
+    #[test]
+    fn catches_up_after_long_ticks() {
+        let mut fixed_step = FixedStep::new(60);
+
+        assert_eq!(fixed_step.advance(10.0), 0);
+        assert_eq!(fixed_step.advance(40.0), 3);
+    }
+}
@@ -1,6 +1,13 @@
 #![allow(dead_code)]
 const CHAR_SIZE: usize = 4 * 6;
-type Character = [u8; CHAR_SIZE];
+pub type Character = [u8; CHAR_SIZE];
+
+/// Horizontal space taken by a character.
+pub const CHAR_WIDTH: i32 = 4;
+/// Vertical space taken by a line of text.
+pub const LINE_HEIGHT: i32 = 6;
+/// Tab stops are every 4 characters.
+pub const TAB_WIDTH: i32 = 4 * CHAR_WIDTH;
 
 pub const MISSING_CHAR: Character = [
     1, 1, 1, 1, //
@@ -495,11 +502,311 @@ const LETTERS: [Character; 26] = [
     ],
 ];
 
+/// Pico8's "puny" letters, drawn for uppercase characters: small caps one pixel shorter
+/// than [`LETTERS`].
+const PUNY_LETTERS: [Character; 26] = [
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        1, 0, 1, 0, //
+        1, 1, 1, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 0, 0, //
+        1, 1, 1, 0, //
+        1, 0, 1, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 1, 1, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        1, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        1, 1, 0, 0, //
+        1, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        1, 1, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 1, 1, 0, //
+        1, 0, 0, 0, //
+        1, 0, 1, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        1, 1, 1, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        1, 1, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        1, 1, 1, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 1, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        1, 0, 1, 0, //
+        1, 1, 1, 0, //
+        1, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 1, 0, 0, //
+        1, 0, 1, 0, //
+        1, 1, 0, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 0, 0, //
+        1, 0, 1, 0, //
+        1, 1, 0, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 1, 1, 0, //
+        1, 0, 0, 0, //
+        0, 0, 1, 0, //
+        1, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        1, 1, 1, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 0, 1, 0, //
+        1, 0, 1, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 1, 0, //
+        1, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+];
+
+const SYMBOLS3: [Character; 6] = [
+    [
+        1, 1, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        0, 1, 0, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 1, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 0, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 0, 0, //
+        0, 0, 1, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+];
+
+const SYMBOLS4: [Character; 4] = [
+    [
+        0, 1, 1, 0, //
+        0, 1, 0, 0, //
+        1, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        1, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 1, 0, //
+        0, 1, 0, 0, //
+        1, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 0, 1, 0, //
+        1, 1, 1, 0, //
+        1, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+];
+
 pub const FIRST_CHAR: char = ' ';
 
-pub const FONT: [Character; SYMBOLS1.len() + NUMBERS.len() + SYMBOLS2.len() + LETTERS.len()] = {
-    let arr = [[0; CHAR_SIZE]; SYMBOLS1.len() + NUMBERS.len() + SYMBOLS2.len() + LETTERS.len()];
-    copy(arr, &[&SYMBOLS1, &NUMBERS, &SYMBOLS2, &LETTERS])
+pub const FONT: [Character; SYMBOLS1.len() + NUMBERS.len() + SYMBOLS2.len() + PUNY_LETTERS.len()] = {
+    let arr =
+        [[0; CHAR_SIZE]; SYMBOLS1.len() + NUMBERS.len() + SYMBOLS2.len() + PUNY_LETTERS.len()];
+    copy(arr, &[&SYMBOLS1, &NUMBERS, &SYMBOLS2, &PUNY_LETTERS])
 };
 
 const fn copy<const N: usize>(mut arr: [Character; N], arrays: &[&[Character]]) -> [Character; N] {
@@ -520,3 +827,44 @@ const fn copy<const N: usize>(mut arr: [Character; N], arrays: &[&[Character]])
 
     arr
 }
+
+/// Glyph used to draw `char`, or `None` for (zero-width) control characters.
+///
+/// Like in pico8, lowercase letters are drawn with the regular font
+/// and uppercase letters with the smaller "puny" one.
+/// Characters pico8 can't show are drawn as a filled block.
+pub fn glyph(char: char) -> Option<&'static Character> {
+    let offset = |first: char| char as usize - first as usize;
+
+    match char {
+        '\0'..='\x1f' => None,
+        FIRST_CHAR..='Z' => FONT.get(offset(FIRST_CHAR)),
+        '['..='`' => SYMBOLS3.get(offset('[')),
+        'a'..='z' => LETTERS.get(offset('a')),
+        '{'..='~' => SYMBOLS4.get(offset('{')),
+        _ => Some(&MISSING_CHAR),
+    }
+}
+
+/// P8SCII control codes understood by `print`.
+///
+/// Parameters follow the code as a single character: `0-9` and `a-v` stand for 0 to 31.
+/// See: <https://pico-8.fandom.com/wiki/P8SCII_Control_Codes>
+pub mod control {
+    /// `REPEAT n`: draws the next character `n` times.
+    pub const REPEAT: char = '\x01';
+    /// `BACKGROUND c`: draws a background of color `c` behind the following characters.
+    pub const BACKGROUND: char = '\x02';
+    /// `MOVE_X n`: moves the cursor `n - 16` pixels horizontally.
+    pub const MOVE_X: char = '\x03';
+    /// `MOVE_Y n`: moves the cursor `n - 16` pixels vertically.
+    pub const MOVE_Y: char = '\x04';
+    /// `MOVE x y`: moves the cursor `x - 16`, `y - 16` pixels.
+    pub const MOVE: char = '\x05';
+    /// Moves the cursor one character back.
+    pub const BACKSPACE: char = '\x08';
+    /// `FOREGROUND c`: draws the following characters in color `c`.
+    pub const FOREGROUND: char = '\x0c';
+    /// Moves the cursor to the start of the line.
+    pub const CARRIAGE_RETURN: char = '\r';
+}
@@ -1,80 +1,105 @@
-use crate::{Button, InputEvent, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent};
+use std::collections::HashSet;
+
+use crate::{
+    Button, InputEvent, Key, KeyBindings, KeyState, KeyboardEvent, MouseButton, MouseEvent,
+    MAX_PLAYERS,
+};
 
 #[derive(Debug)]
 pub struct Input {
-    pub(crate) left: Option<bool>,
-    pub(crate) right: Option<bool>,
-    pub(crate) up: Option<bool>,
-    pub(crate) down: Option<bool>,
-    pub(crate) x: Option<bool>,
-    pub(crate) c: Option<bool>,
+    /// Each player's buttons, in the order of [`Button::PLAYER_BUTTONS`].
+    pub(crate) buttons: [[Option<bool>; Button::PLAYER_BUTTONS.len()]; MAX_PLAYERS],
     pub mouse: Option<bool>,
+    pub(crate) right_mouse: Option<bool>,
+    pub(crate) middle_mouse: Option<bool>,
+    /// Scroll wheel steps since the last update.
+    pub(crate) wheel: i32,
     pub mouse_x: i32,
     pub mouse_y: i32,
+    /// Characters typed since the last update.
+    pub(crate) typed: Vec<char>,
+    key_bindings: KeyBindings,
+    /// Keys currently held down, so a button stays pressed while any of its keys is.
+    held_keys: HashSet<Key>,
 }
 
 #[allow(clippy::new_without_default)]
 impl Input {
+    /// Input with pico8's default [`KeyBindings`].
     pub fn new() -> Self {
+        Self::with_key_bindings(KeyBindings::default())
+    }
+
+    pub fn with_key_bindings(key_bindings: KeyBindings) -> Self {
         Self {
-            left: None,
-            right: None,
-            up: None,
-            down: None,
-            x: None,
-            c: None,
+            buttons: [[None; Button::PLAYER_BUTTONS.len()]; MAX_PLAYERS],
             mouse: None,
+            right_mouse: None,
+            middle_mouse: None,
+            wheel: 0,
             // TODO: Initialize mouse properly
             mouse_x: 64,
             mouse_y: 64,
+            typed: Vec::new(),
+            key_bindings,
+            held_keys: HashSet::new(),
         }
     }
 
     pub fn on_event(&mut self, event: InputEvent) {
         match event {
             InputEvent::Keyboard(KeyboardEvent { key, state }) => {
-                if let Some(button) = key_to_button(key) {
-                    let key_ref = self.button_to_ref(button);
-                    *key_ref = Some(state == KeyState::Down);
+                match state {
+                    KeyState::Down => self.held_keys.insert(key),
+                    KeyState::Up => self.held_keys.remove(&key),
+                };
+
+                if let Some((player, button)) = self.key_bindings.button(key) {
+                    let held = self
+                        .key_bindings
+                        .keys(player, button)
+                        .iter()
+                        .any(|key| self.held_keys.contains(key));
+                    *self.button_to_ref(button, player) = Some(held);
                 }
             }
-            InputEvent::Mouse(MouseEvent::Button {
-                button: MouseButton::Left,
-                state,
-            }) => {
-                self.mouse = Some(state == KeyState::Down);
+            InputEvent::Mouse(MouseEvent::Button { button, state }) => {
+                let button_ref = match button {
+                    MouseButton::Left => &mut self.mouse,
+                    MouseButton::Right => &mut self.right_mouse,
+                    MouseButton::Middle => &mut self.middle_mouse,
+                };
+                *button_ref = Some(state == KeyState::Down);
             }
             InputEvent::Mouse(MouseEvent::Move { x, y }) => {
                 self.mouse_x = x;
                 self.mouse_y = y;
             }
-            InputEvent::Mouse(MouseEvent::Button { .. }) => {
-                // Runty8 games currently can't access other mouse buttons
+            InputEvent::Mouse(MouseEvent::Wheel { delta }) => {
+                self.wheel += delta;
             }
         }
     }
 
-    fn button_to_ref(&mut self, button: Button) -> &mut Option<bool> {
-        match button {
-            Button::Cross => &mut self.x,
-            Button::Circle => &mut self.c,
-            Button::Left => &mut self.left,
-            Button::Right => &mut self.right,
-            Button::Up => &mut self.up,
-            Button::Down => &mut self.down,
-            Button::Mouse => &mut self.mouse,
+    /// Registers a typed character, see [`Event::Text`](crate::Event::Text).
+    ///
+    /// Like pico8, Enter is `'\r'`, Backspace is `'\u{8}'` and Tab is `'\t'`.
+    /// Other control characters are ignored.
+    pub fn on_text(&mut self, character: char) {
+        let character = match character {
+            '\n' => '\r',
+            character => character,
+        };
+
+        if !character.is_control() || matches!(character, '\r' | '\u{8}' | '\t') {
+            self.typed.push(character);
         }
     }
-}
 
-fn key_to_button(key: Key) -> Option<Button> {
-    match key {
-        Key::X => Some(Button::Cross),
-        Key::C => Some(Button::Circle),
-        Key::LeftArrow => Some(Button::Left),
-        Key::RightArrow => Some(Button::Right),
-        Key::UpArrow => Some(Button::Up),
-        Key::DownArrow => Some(Button::Down),
-        _ => None,
+    fn button_to_ref(&mut self, button: Button, player: usize) -> &mut Option<bool> {
+        match button.index() {
+            Some(index) => &mut self.buttons[player][index],
+            None => &mut self.mouse,
+        }
     }
 }
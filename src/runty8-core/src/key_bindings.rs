@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use crate::serialize::Serialize;
+use crate::{Button, Key, MAX_PLAYERS};
+
+/// Keys that press each player's buttons. A button can have several keys.
+///
+/// The default bindings are pico8's:
+///
+/// | Player | Left | Right | Up | Down | O (circle) | X (cross) |
+/// |--------|------|-------|----|------|------------|-----------|
+/// | 0      | ←    | →     | ↑  | ↓    | Z, C, N    | X, V, M   |
+/// | 1      | S    | F     | E  | D    | Shift, Tab | A, Q      |
+///
+/// They can be loaded from a config file with a line per button: the player, the button
+/// (`left`, `right`, `up`, `down`, `o` or `x`) and its keys. Empty lines and lines starting
+/// with `#` are ignored:
+///
+/// ```
+/// # use runty8_core::{Button, Key, KeyBindings};
+/// let key_bindings = KeyBindings::deserialize(
+///     "# AZERTY
+///     0 left LeftArrow Q
+///     0 right RightArrow D
+///     0 up UpArrow Z
+///     0 down DownArrow S
+///     0 o C
+///     0 x X",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(key_bindings.button(Key::Z), Some((0, Button::Up)));
+/// assert_eq!(key_bindings.keys(0, Button::Left), [Key::Q, Key::LeftArrow]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: BTreeMap<Key, (usize, Button)>,
+}
+
+impl KeyBindings {
+    /// Bindings without any keys.
+    pub fn empty() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    /// Makes `key` press `player`'s `button`, instead of whatever it pressed before.
+    ///
+    /// Panics if `player` isn't below [`MAX_PLAYERS`].
+    pub fn bind(&mut self, key: Key, player: usize, button: Button) -> &mut Self {
+        assert!(
+            player < MAX_PLAYERS,
+            "Invalid player: {player}, players go from 0 to {}",
+            MAX_PLAYERS - 1
+        );
+        self.bindings.insert(key, (player, button));
+        self
+    }
+
+    /// Makes `key` not press any button.
+    pub fn unbind(&mut self, key: Key) -> &mut Self {
+        self.bindings.remove(&key);
+        self
+    }
+
+    /// Player and button pressed by `key`, if any.
+    pub fn button(&self, key: Key) -> Option<(usize, Button)> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// Keys that press `player`'s `button`.
+    pub fn keys(&self, player: usize, button: Button) -> Vec<Key> {
+        self.bindings
+            .iter()
+            .filter(|(_, &binding)| binding == (player, button))
+            .map(|(&key, _)| key)
+            .collect()
+    }
+
+    /// Reads bindings saved in `file_path` (from `localStorage`, on wasm).
+    pub fn load(file_path: &str) -> Result<Self, String> {
+        let contents =
+            crate::read(file_path).ok_or_else(|| format!("Couldn't read {file_path}"))?;
+
+        Self::deserialize(&contents)
+    }
+
+    pub fn deserialize(file_contents: &str) -> Result<Self, String> {
+        let mut key_bindings = Self::empty();
+
+        for line in file_contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let (Some(player), Some(button)) = (words.next(), words.next()) else {
+                return Err(format!("Expected a player and a button in: {line:?}"));
+            };
+
+            let player = player
+                .parse::<usize>()
+                .ok()
+                .filter(|&player| player < MAX_PLAYERS)
+                .ok_or_else(|| format!("Invalid player: {player:?}"))?;
+            let button =
+                button_from_name(button).ok_or_else(|| format!("Invalid button: {button:?}"))?;
+
+            for key in words {
+                let key = key_from_name(key).ok_or_else(|| format!("Invalid key: {key:?}"))?;
+                key_bindings.bind(key, player, button);
+            }
+        }
+
+        Ok(key_bindings)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use Button::*;
+
+        let mut key_bindings = Self::empty();
+        let players = [
+            [
+                (Left, &[Key::LeftArrow][..]),
+                (Right, &[Key::RightArrow]),
+                (Up, &[Key::UpArrow]),
+                (Down, &[Key::DownArrow]),
+                (Circle, &[Key::Z, Key::C, Key::N]),
+                (Cross, &[Key::X, Key::V, Key::M]),
+            ],
+            [
+                (Left, &[Key::S]),
+                (Right, &[Key::F]),
+                (Up, &[Key::E]),
+                (Down, &[Key::D]),
+                (Circle, &[Key::Shift, Key::Tab]),
+                (Cross, &[Key::A, Key::Q]),
+            ],
+        ];
+
+        for (player, buttons) in players.iter().enumerate() {
+            for &(button, keys) in buttons {
+                for &key in keys {
+                    key_bindings.bind(key, player, button);
+                }
+            }
+        }
+
+        key_bindings
+    }
+}
+
+impl Serialize for KeyBindings {
+    fn serialize(&self) -> String {
+        (0..MAX_PLAYERS)
+            .cartesian_product(Button::PLAYER_BUTTONS)
+            .filter_map(|(player, button)| {
+                let keys = self.keys(player, button);
+
+                (!keys.is_empty()).then(|| {
+                    let keys = keys.iter().map(|key| format!("{key:?}")).join(" ");
+
+                    format!("{player} {} {keys}", button_name(button))
+                })
+            })
+            .join("\n")
+    }
+}
+
+fn button_name(button: Button) -> &'static str {
+    match button {
+        Button::Left => "left",
+        Button::Right => "right",
+        Button::Up => "up",
+        Button::Down => "down",
+        Button::Circle => "o",
+        Button::Cross => "x",
+        Button::Mouse => "mouse",
+    }
+}
+
+fn button_from_name(name: &str) -> Option<Button> {
+    Button::PLAYER_BUTTONS
+        .into_iter()
+        .find(|&button| button_name(button) == name)
+}
+
+fn key_from_name(name: &str) -> Option<Key> {
+    Key::ALL.into_iter().find(|key| format!("{key:?}") == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyBindings;
+    use crate::serialize::Serialize;
+    use crate::{Button, Key};
+
+    #[test]
+    fn defaults_to_pico8s_keys() {
+        let key_bindings = KeyBindings::default();
+
+        assert_eq!(
+            key_bindings.keys(0, Button::Circle),
+            [Key::C, Key::N, Key::Z]
+        );
+        assert_eq!(
+            key_bindings.keys(0, Button::Cross),
+            [Key::M, Key::V, Key::X]
+        );
+        assert_eq!(key_bindings.button(Key::E), Some((1, Button::Up)));
+        assert_eq!(key_bindings.button(Key::Tab), Some((1, Button::Circle)));
+        assert_eq!(key_bindings.button(Key::P), None);
+    }
+
+    #[test]
+    fn binding_a_key_replaces_its_previous_button() {
+        let mut key_bindings = KeyBindings::default();
+        key_bindings.bind(Key::Z, 0, Button::Up).unbind(Key::C);
+
+        assert_eq!(key_bindings.button(Key::Z), Some((0, Button::Up)));
+        assert_eq!(key_bindings.keys(0, Button::Circle), [Key::N]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid player: 8")]
+    fn binding_a_missing_player_panics() {
+        KeyBindings::empty().bind(Key::P, 8, Button::Left);
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let key_bindings = KeyBindings::default();
+        let serialized = key_bindings.serialize();
+
+        assert!(serialized.contains("0 o C N Z"));
+        assert_eq!(KeyBindings::deserialize(&serialized), Ok(key_bindings));
+    }
+
+    #[test]
+    fn deserialize_reports_invalid_lines() {
+        assert!(KeyBindings::deserialize("0 jump Space").is_err());
+        assert!(KeyBindings::deserialize("8 left A").is_err());
+        assert!(KeyBindings::deserialize("0 left Banana").is_err());
+        assert!(KeyBindings::deserialize("0").is_err());
+        assert_eq!(
+            KeyBindings::deserialize("\n# Nothing\n"),
+            Ok(KeyBindings::empty())
+        );
+    }
+}
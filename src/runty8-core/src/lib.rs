@@ -2,30 +2,45 @@
 
 //! Types and functions required to run a Runty8 game.
 
+mod cartdata;
 mod draw_data;
+mod fixed;
+mod fixed_step;
 mod flags;
 mod input;
+mod key_bindings;
 mod map;
+pub mod memory;
 mod pico8;
+mod rng;
 pub mod serialize;
 mod sprite_sheet;
 mod state;
 pub(crate) mod util;
-pub use draw_data::colors;
+pub use draw_data::{colors, EXTENDED_COLORS_START};
 
+pub mod audio;
 pub mod draw;
 pub mod font;
 
+pub use audio::{Music, SoundEffects};
 pub use draw_data::DrawData;
+pub use fixed::Fixed;
+pub use fixed_step::FixedStep;
 pub use flags::Flags;
 pub use input::Input;
-pub use map::Map;
+pub use key_bindings::KeyBindings;
+pub use map::{Map, MapOptions};
 pub use pico8::*;
 use serialize::{Serialize, Serialized};
 pub use sprite_sheet::{Sprite, SpriteSheet};
 
 /// A regular pico8 app.
 pub trait App {
+    /// How many times per second `update` and `draw` are called. Pico8 games run at 30,
+    /// or at 60 if they define `_update60` instead of `_update`.
+    const FPS: u32 = 30;
+
     fn init(pico8: &mut Pico8) -> Self;
     fn update(&mut self, pico8: &mut Pico8);
     fn draw(&mut self, pico8: &mut Pico8);
@@ -37,7 +52,7 @@ pub trait App {
 pub type Color = u8; // Actually a u4
 
 /// Pico8's supported input buttons.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Button {
     /// Left arrow.
     Left,
@@ -55,7 +70,29 @@ pub enum Button {
     Mouse,
 }
 
-/// Game assets: sprite sheet, map, flags.
+impl Button {
+    /// Buttons each player has, in the order of their bits in [`Pico8::btn_bitfield`].
+    pub const PLAYER_BUTTONS: [Button; 6] = [
+        Button::Left,
+        Button::Right,
+        Button::Up,
+        Button::Down,
+        Button::Circle,
+        Button::Cross,
+    ];
+
+    /// Position in [`Button::PLAYER_BUTTONS`], `None` for [`Button::Mouse`].
+    pub(crate) fn index(self) -> Option<usize> {
+        Self::PLAYER_BUTTONS
+            .iter()
+            .position(|&button| button == self)
+    }
+}
+
+/// How many players can play at the same time, see [`Pico8::btn_`].
+pub const MAX_PLAYERS: usize = 8;
+
+/// Game assets: sprite sheet, map, flags, sound effects, music.
 // TODO: Rename to assets?
 #[derive(Debug)]
 pub struct Resources {
@@ -63,9 +100,23 @@ pub struct Resources {
     pub sprite_sheet: SpriteSheet,
     pub sprite_flags: Flags,
     pub map: Map,
+    pub sound_effects: SoundEffects,
+    pub music: Music,
 }
 
 impl Resources {
+    /// Default assets and an empty assets path, for games (and tests) that don't load any.
+    pub fn empty() -> Self {
+        Self {
+            assets_path: String::new(),
+            sprite_sheet: SpriteSheet::new(),
+            sprite_flags: Flags::new(),
+            map: Map::new(),
+            sound_effects: SoundEffects::default(),
+            music: Music::default(),
+        }
+    }
+
     pub fn serialize(&self) -> Vec<Serialized> {
         vec![
             Serialized {
@@ -80,6 +131,14 @@ impl Resources {
                 file_name: Flags::file_name(),
                 serialized: self.sprite_flags.serialize(),
             },
+            Serialized {
+                file_name: SoundEffects::file_name(),
+                serialized: self.sound_effects.serialize(),
+            },
+            Serialized {
+                file_name: Music::file_name(),
+                serialized: self.music.serialize(),
+            },
         ]
     }
 }
@@ -91,7 +150,7 @@ pub enum KeyState {
     Down,
 }
 /// Keyboard keys.
-#[derive(Clone, Copy, Debug, PartialEq, Hash, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Hash, Eq, PartialOrd, Ord)]
 pub enum Key {
     ///
     A,
@@ -145,7 +204,7 @@ pub enum Key {
     Y,
     ///
     Z,
-    ///
+    /// Either control key.
     Control,
     ///
     LeftArrow,
@@ -157,10 +216,163 @@ pub enum Key {
     DownArrow,
     ///
     Escape,
-    ///
+    /// Either alt key.
     Alt,
     ///
     Space,
+    Tab,
+    /// Either shift key.
+    Shift,
+    /// `0`, on the number row or the numpad.
+    Digit0,
+    /// `1`, on the number row or the numpad.
+    Digit1,
+    /// `2`, on the number row or the numpad.
+    Digit2,
+    /// `3`, on the number row or the numpad.
+    Digit3,
+    /// `4`, on the number row or the numpad.
+    Digit4,
+    /// `5`, on the number row or the numpad.
+    Digit5,
+    /// `6`, on the number row or the numpad.
+    Digit6,
+    /// `7`, on the number row or the numpad.
+    Digit7,
+    /// `8`, on the number row or the numpad.
+    Digit8,
+    /// `9`, on the number row or the numpad.
+    Digit9,
+    /// Either enter key, on the main keyboard or the numpad.
+    Enter,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    /// `-`
+    Minus,
+    /// `=`
+    Equals,
+    /// `[`
+    LeftBracket,
+    /// `]`
+    RightBracket,
+    /// `\`
+    Backslash,
+    /// `;`
+    Semicolon,
+    /// `'`, the key right of `;` on US layouts.
+    Apostrophe,
+    /// `` ` ``, the key left of `1` on US layouts.
+    Grave,
+    /// `,`
+    Comma,
+    /// `.`
+    Period,
+    /// `/`
+    Slash,
+}
+
+impl Key {
+    /// Every key, in declaration order.
+    pub const ALL: [Key; 77] = {
+        use Key::*;
+
+        [
+            A,
+            B,
+            C,
+            D,
+            E,
+            F,
+            G,
+            H,
+            I,
+            J,
+            K,
+            L,
+            M,
+            N,
+            O,
+            P,
+            Q,
+            R,
+            S,
+            T,
+            U,
+            V,
+            W,
+            X,
+            Y,
+            Z,
+            Control,
+            LeftArrow,
+            RightArrow,
+            UpArrow,
+            DownArrow,
+            Escape,
+            Alt,
+            Space,
+            Tab,
+            Shift,
+            Digit0,
+            Digit1,
+            Digit2,
+            Digit3,
+            Digit4,
+            Digit5,
+            Digit6,
+            Digit7,
+            Digit8,
+            Digit9,
+            Enter,
+            Backspace,
+            Delete,
+            Insert,
+            Home,
+            End,
+            PageUp,
+            PageDown,
+            F1,
+            F2,
+            F3,
+            F4,
+            F5,
+            F6,
+            F7,
+            F8,
+            F9,
+            F10,
+            F11,
+            F12,
+            Minus,
+            Equals,
+            LeftBracket,
+            RightBracket,
+            Backslash,
+            Semicolon,
+            Apostrophe,
+            Grave,
+            Comma,
+            Period,
+            Slash,
+        ]
+    };
 }
 
 /// Keyboard event (key up/down).
@@ -192,7 +404,7 @@ pub enum MouseButton {
     Right,
 }
 
-/// Mouse events (mouse move, button presses).
+/// Mouse events (mouse move, button presses, scroll wheel).
 #[derive(Clone, Copy, Debug)]
 pub enum MouseEvent {
     /// Mouse move event.
@@ -210,6 +422,11 @@ pub enum MouseEvent {
         /// Whether the button was pressed or released.
         state: KeyState,
     },
+    /// Scroll wheel turned.
+    Wheel {
+        /// Steps scrolled: positive when scrolling up, negative when scrolling down.
+        delta: i32,
+    },
 }
 
 /// Runty8 events (input, tick, etc).
@@ -222,6 +439,8 @@ pub enum Event {
         /// How much time passed since the last [`Event::Tick`], in milliseconds.
         delta_millis: f64,
     },
+    /// A character was typed, taking the keyboard layout and modifiers into account.
+    Text(char),
     // TODO: Remove this
     WindowClosed,
 }
@@ -274,7 +493,7 @@ fn load(_file_path: &str) -> Option<String> {
 
     #[cfg(target_arch = "wasm32")]
     {
-        let wasm_contents = wasm::load(_file_path);
+        let wasm_contents = read(_file_path);
         log::info!(
             "Loading assets from: {}... {}.",
             _file_path,
@@ -288,6 +507,16 @@ fn load(_file_path: &str) -> Option<String> {
     }
 }
 
+/// Reads a file stored with [`serialize::write`](crate::serialize):
+///  - Native: Uses regular `std::fs::read_to_string`
+///  - Web: Uses `localStorage.getItem`
+pub(crate) fn read(file_path: &str) -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::fs::read_to_string(file_path).ok();
+    #[cfg(target_arch = "wasm32")]
+    return wasm::load(file_path);
+}
+
 pub fn load_file(
     dir: &include_dir::Dir,
     assets_path: &str,
@@ -331,6 +560,10 @@ macro_rules! load_assets {
                 $crate::load_file(&DIR, &assets_path, &$crate::Flags::file_name())?;
             let sprite_sheet_contents =
                 $crate::load_file(&DIR, &assets_path, &$crate::SpriteSheet::file_name())?;
+            let sound_effects_contents =
+                $crate::load_file(&DIR, &assets_path, &$crate::SoundEffects::file_name())?;
+            let music_contents =
+                $crate::load_file(&DIR, &assets_path, &$crate::Music::file_name())?;
 
             let map =
                 $crate::create_asset($crate::Map::deserialize, "map", map_contents.as_deref())?;
@@ -347,10 +580,24 @@ macro_rules! load_assets {
                 sprite_sheet_contents.as_deref(),
             )?;
 
+            let sound_effects = $crate::create_asset(
+                $crate::SoundEffects::deserialize,
+                "sound effects",
+                sound_effects_contents.as_deref(),
+            )?;
+
+            let music = $crate::create_asset(
+                $crate::Music::deserialize,
+                "music",
+                music_contents.as_deref(),
+            )?;
+
             Ok::<$crate::Resources, String>($crate::Resources {
                 map,
                 sprite_flags,
                 sprite_sheet,
+                sound_effects,
+                music,
                 assets_path,
             })
         })()
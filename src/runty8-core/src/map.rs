@@ -1,11 +1,14 @@
 use crate::serialize::Serialize;
 
-use super::sprite_sheet::Sprite;
+use super::sprite_sheet::{Sprite, SpriteSheet};
 use itertools::Itertools;
 
 type SpriteId = u8;
 
 /// A pico8 game's sprite map.
+///
+/// Only its top half is stored here, the bottom half is the bottom half of the
+/// [`SpriteSheet`], like in pico8.
 #[derive(Debug, Clone)]
 pub struct Map {
     // Don't really want the size to change
@@ -21,12 +24,18 @@ impl Map {
 impl Map {
     const SCREEN_SIZE_PIXELS: usize = 128;
     const SCREENS_WIDTH: usize = 8; // map is 8 screens wide
-    const SCREENS_HEIGHT: usize = 4; // map is 4 screens tall (actually 2, bottom 2 are shared with spritesheet)
+
+    // Map is 4 screens tall. Like in pico8, the bottom 2 share memory with the sprite sheet,
+    // so only the top 2 are stored in the map.
+    const SCREENS_HEIGHT: usize = 4;
+    const OWN_SCREENS_HEIGHT: usize = 2;
 
     const SPRITES_PER_SCREEN_ROW: usize = Self::SCREEN_SIZE_PIXELS / Sprite::WIDTH;
     pub const WIDTH_SPRITES: usize = Self::SCREENS_WIDTH * Self::SPRITES_PER_SCREEN_ROW;
     pub const HEIGHT_SPRITES: usize = Self::SCREENS_HEIGHT * Self::SPRITES_PER_SCREEN_ROW;
-    const MAP_SIZE: usize = Self::WIDTH_SPRITES * Self::HEIGHT_SPRITES;
+    /// Rows stored in the map, the rest are in the sprite sheet.
+    pub const OWN_HEIGHT_SPRITES: usize = Self::OWN_SCREENS_HEIGHT * Self::SPRITES_PER_SCREEN_ROW;
+    const MAP_SIZE: usize = Self::WIDTH_SPRITES * Self::OWN_HEIGHT_SPRITES;
 
     // TODO: Make pub(crate)
     pub fn new() -> Self {
@@ -39,20 +48,28 @@ impl Map {
         Map { map }
     }
 
-    pub fn mget(&self, cel_x: i32, cel_y: i32) -> u8 {
-        let index = Self::index(cel_x, cel_y);
-
-        // TODO: Handle like pico8
-        // TODO2: I think it returns 0 if outside bounds?
-        index.map(|index| self.map[index]).unwrap_or(0)
+    /// Sprite at the given cell, 0 if it's outside the map.
+    /// Cells in the bottom half are read from `sprite_sheet`.
+    pub fn mget(&self, sprite_sheet: &SpriteSheet, cel_x: i32, cel_y: i32) -> u8 {
+        match Self::index(cel_x, cel_y) {
+            Some(index) if index < Self::MAP_SIZE => self.map[index],
+            // The bottom half starts at sprite sheet byte 0x1000, which is `MAP_SIZE`,
+            // so cells and sprite sheet bytes have the same index.
+            Some(index) => sprite_sheet.peek(index),
+            None => 0,
+        }
     }
 
-    pub fn mset(&mut self, cel_x: usize, cel_y: usize, sprite: u8) {
+    /// Sets the sprite at the given cell. Cells in the bottom half are written to `sprite_sheet`.
+    pub fn mset(&mut self, sprite_sheet: &mut SpriteSheet, cel_x: usize, cel_y: usize, sprite: u8) {
         let index = cel_x + cel_y * Map::WIDTH_SPRITES;
         // TODO: Handle like pico8
-        assert!(index <= self.map.len());
+        assert!(index < Self::WIDTH_SPRITES * Self::HEIGHT_SPRITES);
 
-        self.map[index] = sprite;
+        match self.map.get_mut(index) {
+            Some(cell) => *cell = sprite,
+            None => sprite_sheet.poke(index, sprite),
+        }
     }
 
     fn index(x: i32, y: i32) -> Option<usize> {
@@ -99,3 +116,45 @@ impl Default for Map {
         Self::new()
     }
 }
+
+/// Arguments to pico8's `map`, see [`Pico8::map_with`](crate::Pico8::map_with).
+///
+/// The defaults draw the whole map at the top-left corner of the screen.
+/// ```
+/// # use runty8_core::MapOptions;
+/// let room = MapOptions {
+///     cell_x: 16,
+///     cell_w: 16,
+///     cell_h: 16,
+///     ..MapOptions::default()
+/// };
+/// # assert_eq!(room.layer, 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapOptions {
+    /// Top-left cell of the map to draw.
+    pub cell_x: i32,
+    pub cell_y: i32,
+    /// Where on the screen to draw it.
+    pub screen_x: i32,
+    pub screen_y: i32,
+    /// How many cells to draw.
+    pub cell_w: i32,
+    pub cell_h: i32,
+    /// Only sprites with all of these flags are drawn, 0 draws all of them.
+    pub layer: u8,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self {
+            cell_x: 0,
+            cell_y: 0,
+            screen_x: 0,
+            screen_y: 0,
+            cell_w: Map::WIDTH_SPRITES as i32,
+            cell_h: Map::HEIGHT_SPRITES as i32,
+            layer: 0,
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Pico8's 32KiB of addressable memory, see <https://pico-8.fandom.com/wiki/Memory>.
+//!
+//! Runty8 doesn't store its state as raw bytes, so most of the address space is a view
+//! over the assets and the draw state:
+//!
+//! | Addresses         | Backed by                                   |
+//! |-------------------|---------------------------------------------|
+//! | `0x0000..0x2000`  | Sprite sheet, 2 pixels per byte             |
+//! | `0x1000..0x2000`  | Also map rows 32 to 63, a byte per cell     |
+//! | `0x2000..0x3000`  | Map, rows 0 to 31                           |
+//! | `0x3000..0x3100`  | Sprite flags                                |
+//! | `0x5f00..0x5f40`  | Draw state: palettes, clip, cursor, camera… |
+//! | `0x5f5c..0x5f5e`  | `btnp` repeat delay and interval            |
+//! | `0x6000..0x8000`  | Screen, 2 pixels per byte                   |
+//!
+//! Everything else is plain memory.
+use std::ops::Range;
+
+use crate::draw_data::DrawData;
+use crate::state::{BtnpRepeat, State};
+use crate::{Map, Resources};
+
+/// Size of the address space, in bytes.
+pub const MEMORY_SIZE: usize = 0x8000;
+
+const SPRITE_SHEET: Range<usize> = 0x0000..0x2000;
+const MAP: Range<usize> = 0x2000..0x3000;
+const FLAGS: Range<usize> = 0x3000..0x3100;
+const DRAW_STATE: Range<usize> = 0x5f00..0x5f40;
+const BTNP_DELAY: usize = 0x5f5c;
+const BTNP_INTERVAL: usize = 0x5f5d;
+const SCREEN: Range<usize> = 0x6000..0x8000;
+
+/// Bytes of memory that aren't backed by anything else.
+#[derive(Debug)]
+pub(crate) struct Memory {
+    ram: Box<[u8; MEMORY_SIZE]>,
+}
+
+impl Memory {
+    pub(crate) fn new() -> Self {
+        Self {
+            ram: Box::new([0; MEMORY_SIZE]),
+        }
+    }
+
+    /// Byte at `address`, 0 if it's outside of the address space.
+    pub(crate) fn peek(
+        &self,
+        address: usize,
+        resources: &Resources,
+        draw_data: &DrawData,
+        state: &State,
+    ) -> u8 {
+        if SPRITE_SHEET.contains(&address) {
+            resources.sprite_sheet.peek(address - SPRITE_SHEET.start)
+        } else if MAP.contains(&address) {
+            let (x, y) = map_location(address);
+
+            resources
+                .map
+                .mget(&resources.sprite_sheet, x as i32, y as i32)
+        } else if FLAGS.contains(&address) {
+            resources
+                .sprite_flags
+                .get(address - FLAGS.start)
+                .unwrap_or(0)
+        } else if DRAW_STATE.contains(&address) {
+            draw_data
+                .peek_register(address - DRAW_STATE.start)
+                .unwrap_or(self.ram[address])
+        } else if address == BTNP_DELAY {
+            state.btnp_repeat().delay
+        } else if address == BTNP_INTERVAL {
+            state.btnp_repeat().interval
+        } else if SCREEN.contains(&address) {
+            draw_data.buffer()[address - SCREEN.start]
+        } else {
+            self.ram.get(address).copied().unwrap_or(0)
+        }
+    }
+
+    /// Writes a byte at `address`. Writes outside of the address space are ignored.
+    pub(crate) fn poke(
+        &mut self,
+        address: usize,
+        value: u8,
+        resources: &mut Resources,
+        draw_data: &mut DrawData,
+        state: &mut State,
+    ) {
+        if SPRITE_SHEET.contains(&address) {
+            resources
+                .sprite_sheet
+                .poke(address - SPRITE_SHEET.start, value);
+        } else if MAP.contains(&address) {
+            let (x, y) = map_location(address);
+
+            resources.map.mset(&mut resources.sprite_sheet, x, y, value);
+        } else if FLAGS.contains(&address) {
+            resources
+                .sprite_flags
+                .fset_all(address - FLAGS.start, value);
+        } else if DRAW_STATE.contains(&address) {
+            // Bytes that aren't draw state still behave as memory.
+            self.ram[address] = value;
+            draw_data.poke_register(address - DRAW_STATE.start, value);
+        } else if address == BTNP_DELAY {
+            state.set_btnp_repeat(BtnpRepeat {
+                delay: value,
+                ..state.btnp_repeat()
+            });
+        } else if address == BTNP_INTERVAL {
+            state.set_btnp_repeat(BtnpRepeat {
+                interval: value,
+                ..state.btnp_repeat()
+            });
+        } else if SCREEN.contains(&address) {
+            draw_data.buffer_mut()[address - SCREEN.start] = value;
+        } else if let Some(byte) = self.ram.get_mut(address) {
+            *byte = value;
+        }
+    }
+}
+
+fn map_location(address: usize) -> (usize, usize) {
+    let offset = address - MAP.start;
+
+    (offset % Map::WIDTH_SPRITES, offset / Map::WIDTH_SPRITES)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{colors, Pico8, Resources};
+
+    fn pico8() -> Pico8 {
+        Pico8::new(Resources::empty())
+    }
+
+    #[test]
+    fn poking_the_map_shows_up_in_mget() {
+        let mut pico8 = pico8();
+        pico8.poke(0x2000, 5);
+        pico8.poke(0x2000 + 3 + 2 * 128, 42);
+
+        assert_eq!(pico8.mget(0, 0), 5);
+        assert_eq!(pico8.mget(3, 2), 42);
+
+        pico8.mset(127, 31, 7);
+        assert_eq!(pico8.peek(0x2fff), 7);
+    }
+
+    #[test]
+    fn the_bottom_half_of_the_map_and_the_sprite_sheet_share_memory() {
+        let mut pico8 = pico8();
+        pico8.poke(0x1000, 0x21);
+
+        assert_eq!(pico8.mget(0, 32), 0x21);
+        assert_eq!(pico8.resources.sprite_sheet.get(0, 64), Some(1));
+        assert_eq!(pico8.resources.sprite_sheet.get(1, 64), Some(2));
+
+        pico8.mset(3, 63, 9);
+        assert_eq!(pico8.peek(0x1000 + 3 + 31 * 128), 9);
+    }
+
+    #[test]
+    fn poking_the_flags_shows_up_in_fget() {
+        let mut pico8 = pico8();
+        pico8.poke(0x3000 + 10, 0b1000_0010);
+
+        assert!(!pico8.fget_n(10, 0));
+        assert!(pico8.fget_n(10, 1));
+        assert!(pico8.fget_n(10, 7));
+        assert!(!pico8.fget_n(11, 1));
+
+        pico8.fset(255, 3, true);
+        assert_eq!(pico8.peek(0x30ff), 0b1000);
+    }
+
+    #[test]
+    fn poking_the_screen_shows_up_in_pget() {
+        let mut pico8 = pico8();
+        // Left pixel in the low nibble.
+        pico8.poke(0x6000, 0x8c);
+        pico8.poke(0x6000 + 64 * 5 + 1, 0x30);
+
+        assert_eq!(pico8.pget(0, 0), colors::BLUE);
+        assert_eq!(pico8.pget(1, 0), colors::RED);
+        assert_eq!(pico8.pget(2, 5), colors::BLACK);
+        assert_eq!(pico8.pget(3, 5), colors::DARK_GREEN);
+
+        pico8.pset(127, 127, colors::WHITE);
+        assert_eq!(pico8.peek(0x7fff), 0x70);
+    }
+
+    #[test]
+    fn poking_the_sprite_sheet_shows_up_in_sprites() {
+        let mut pico8 = pico8();
+        // Sprite 1's top-left pixels, then sprite 16's.
+        pico8.poke(0x0004, 0x21);
+        pico8.poke(64 * 8, 0x03);
+
+        pico8.spr(1, 0, 0);
+        pico8.spr(16, 0, 8);
+
+        assert_eq!(pico8.pget(0, 0), 1);
+        assert_eq!(pico8.pget(1, 0), 2);
+        assert_eq!(pico8.pget(0, 8), 3);
+        assert_eq!(pico8.peek(0x0004), 0x21);
+    }
+
+    #[test]
+    fn btnp_repeat_registers() {
+        let mut pico8 = pico8();
+        assert_eq!([pico8.peek(0x5f5c), pico8.peek(0x5f5d)], [0, 0]);
+
+        pico8.poke(0x5f5c, 8);
+        pico8.poke(0x5f5d, 2);
+
+        assert_eq!([pico8.peek(0x5f5c), pico8.peek(0x5f5d)], [8, 2]);
+        assert_eq!(
+            pico8.state.btnp_repeat(),
+            crate::state::BtnpRepeat {
+                delay: 8,
+                interval: 2
+            }
+        );
+    }
+
+    #[test]
+    fn draw_state_registers() {
+        let mut pico8 = pico8();
+
+        // Draw palette: draw red as blue.
+        pico8.poke(0x5f00 + colors::RED as i32, colors::BLUE);
+        pico8.pset(0, 0, colors::RED);
+        assert_eq!(pico8.pget(0, 0), colors::BLUE);
+        assert_eq!(pico8.peek(0x5f00), 0x10, "black is transparent by default");
+
+        // Camera, as 16 bit signed values.
+        pico8.poke2(0x5f28, -10);
+        pico8.poke2(0x5f2a, 3);
+        assert_eq!(pico8.camera(0, 0), (-10, 3));
+
+        pico8.clip(1, 2, 3, 4);
+        assert_eq!(
+            [0x5f20, 0x5f21, 0x5f22, 0x5f23].map(|address| pico8.peek(address)),
+            [1, 2, 4, 6]
+        );
+
+        pico8.fillp(0b1010_0101_1010_0101, true);
+        assert_eq!(pico8.peek2(0x5f31), 0b1010_0101_1010_0101_u16 as i16);
+        assert_eq!(pico8.peek(0x5f33), 1);
+    }
+
+    #[test]
+    fn multi_byte_access_is_little_endian() {
+        let mut pico8 = pico8();
+        pico8.poke4(0x4300, 0x1234_5678);
+
+        assert_eq!(pico8.peek(0x4300), 0x78);
+        assert_eq!(pico8.peek(0x4303), 0x12);
+        assert_eq!(pico8.peek2(0x4302), 0x1234);
+        assert_eq!(pico8.peek4(0x4300), 0x1234_5678);
+    }
+
+    #[test]
+    fn out_of_bounds_accesses_are_ignored() {
+        let mut pico8 = pico8();
+        pico8.poke(-1, 1);
+        pico8.poke(0x8000, 1);
+
+        assert_eq!(pico8.peek(-1), 0);
+        assert_eq!(pico8.peek(0x8000), 0);
+    }
+
+    #[test]
+    fn memcpy_copies_the_sprite_sheet_to_the_screen() {
+        let mut pico8 = pico8();
+        pico8.sset(0, 0, colors::PINK);
+        pico8.sset(127, 63, colors::LIGHT_PEACH);
+
+        // Sprite sheet and screen rows are both 64 bytes long.
+        pico8.memcpy(0x6000, 0x0000, 0x2000);
+
+        assert_eq!(pico8.pget(0, 0), colors::PINK);
+        assert_eq!(pico8.pget(127, 63), colors::LIGHT_PEACH);
+    }
+
+    #[test]
+    fn memcpy_handles_overlapping_ranges() {
+        let mut pico8 = pico8();
+        for i in 0..4 {
+            pico8.poke(0x4300 + i, i as u8 + 1);
+        }
+
+        pico8.memcpy(0x4301, 0x4300, 4);
+        assert_eq!(
+            [0, 1, 2, 3, 4].map(|i| pico8.peek(0x4300 + i)),
+            [1, 1, 2, 3, 4]
+        );
+
+        pico8.memcpy(0x4300, 0x4301, 4);
+        assert_eq!(
+            [0, 1, 2, 3, 4].map(|i| pico8.peek(0x4300 + i)),
+            [1, 2, 3, 4, 4]
+        );
+    }
+
+    #[test]
+    fn memset_fills_the_screen() {
+        let mut pico8 = pico8();
+        pico8.memset(0x6000, 0x11, 0x2000);
+
+        assert_eq!(pico8.pget(0, 0), colors::DARK_BLUE);
+        assert_eq!(pico8.pget(127, 127), colors::DARK_BLUE);
+    }
+}
@@ -1,18 +1,52 @@
 use rand::Rng;
 use std::f32::consts::PI;
 
+use crate::audio::{self, Audio, AudioOutput, Sfx};
+use crate::cartdata::{self, CartData};
 use crate::draw_data::DrawData;
+use crate::map::MapOptions;
+use crate::memory::Memory;
+use crate::rng;
 use crate::sprite_sheet::Sprite;
 use crate::state::State;
-use crate::{Button, Color, Resources};
+use crate::{Button, Color, Fixed, Resources, MAX_PLAYERS};
 
 /// Struct providing an implementation of the pico8 API.
 #[derive(Debug)]
 pub struct Pico8 {
     pub draw_data: DrawData,
     pub state: State,
+    pub audio: Audio,
     pub resources: Resources,
+    memory: Memory,
+    rng: rng::Rng,
+    cart_data: CartData,
     new_title: Option<String>,
+    audio_output: Option<Box<dyn AudioOutput>>,
+    /// Fraction of a sample that [`Pico8::render_audio`] owes to the next frame.
+    audio_leftover: f64,
+}
+
+/// Value returned by [`Pico8::stat`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stat {
+    /// Numeric stats, like the CPU usage, the frame rate or the mouse position.
+    Number(f32),
+    /// Whether something holds, like `stat(30)`'s "there are typed characters left".
+    Bool(bool),
+    /// Text stats, like `stat(31)`'s typed character (or `""` if there are none).
+    Text(String),
+}
+
+impl Stat {
+    /// The stat as a number: booleans are 0 or 1, text is 0.
+    pub fn number(&self) -> f32 {
+        match self {
+            Stat::Number(n) => *n,
+            Stat::Bool(b) => *b as u8 as f32,
+            Stat::Text(_) => 0.0,
+        }
+    }
 }
 
 impl Pico8 {
@@ -20,8 +54,14 @@ impl Pico8 {
         Self {
             draw_data: DrawData::new(),
             state: State::new(),
+            audio: Audio::new(),
             resources,
+            memory: Memory::new(),
+            rng: rng::Rng::new(Fixed::from_bits(rand::thread_rng().gen())),
+            cart_data: CartData::new(),
             new_title: None,
+            audio_output: None,
+            audio_leftover: 0.0,
         }
     }
 }
@@ -29,13 +69,16 @@ impl Pico8 {
 // Public (Pico8) interface
 impl Pico8 {
     pub fn mget(&self, x: i32, y: i32) -> u8 {
-        self.resources.map.mget(x, y)
+        self.resources.map.mget(&self.resources.sprite_sheet, x, y)
     }
 
     pub fn mset(&mut self, x: i32, y: i32, spr: u8) {
-        self.resources
-            .map
-            .mset(x.try_into().unwrap(), y.try_into().unwrap(), spr);
+        self.resources.map.mset(
+            &mut self.resources.sprite_sheet,
+            x.try_into().unwrap(),
+            y.try_into().unwrap(),
+            spr,
+        );
     }
 
     // TODO: Check we do the same left-to-right (or vice versa)
@@ -55,29 +98,101 @@ impl Pico8 {
     }
 
     pub fn btnp(&self, button: Button) -> bool {
-        self.state.button(button).btnp()
+        self.btnp_(button, 0)
     }
 
     pub fn btn(&self, button: Button) -> bool {
-        self.state.button(button).btn()
+        self.btn_(button, 0)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Btnp>
+    ///
+    /// Whether `player` (0 to 7) just pressed `button`. Like pico8, it's true again after
+    /// holding the button for 15 frames, and then every 4 frames. `poke(0x5f5c, delay)` and
+    /// `poke(0x5f5d, interval)` change those (0 for the defaults, a delay of 255 never repeats).
+    pub fn btnp_(&self, button: Button, player: usize) -> bool {
+        self.state.button(button, player).btnp()
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Btn>
+    ///
+    /// Whether `player` (0 to 7) is pressing `button`.
+    pub fn btn_(&self, button: Button, player: usize) -> bool {
+        self.state.button(button, player).btn()
+    }
+
+    /// Pico8's `btn()`: the buttons every player is pressing. Player `p`'s buttons are bits
+    /// `8 * p` to `8 * p + 5`, in the order of [`Button::PLAYER_BUTTONS`].
+    pub fn btn_bitfield(&self) -> u64 {
+        self.bitfield(|pico8, button, player| pico8.btn_(button, player))
+    }
+
+    /// Pico8's `btnp()`: the buttons every player just pressed, like [`Pico8::btn_bitfield`].
+    pub fn btnp_bitfield(&self) -> u64 {
+        self.bitfield(|pico8, button, player| pico8.btnp_(button, player))
+    }
+
+    fn bitfield(&self, is_set: impl Fn(&Self, Button, usize) -> bool) -> u64 {
+        let mut bitfield = 0;
+
+        for player in 0..MAX_PLAYERS {
+            for (bit, &button) in Button::PLAYER_BUTTONS.iter().enumerate() {
+                if is_set(self, button, player) {
+                    bitfield |= 1 << (8 * player + bit);
+                }
+            }
+        }
+
+        bitfield
     }
 
     pub fn pal(&mut self, old: Color, new: Color) {
         self.draw_data.pal(old, new);
     }
 
+    /// <https://pico-8.fandom.com/wiki/Pal>
+    ///
+    /// Remaps `old` to `new` on the draw palette (`palette == 0`),
+    /// or on the screen palette (`palette == 1`), which only changes how the screen is displayed.
+    pub fn pal_(&mut self, old: Color, new: Color, palette: u8) {
+        self.draw_data.pal_(old, new, palette);
+    }
+
     pub fn palt(&mut self, transparent_color: Option<Color>) {
         self.draw_data.palt(transparent_color);
     }
 
+    /// <https://pico-8.fandom.com/wiki/Palt>
+    ///
+    /// Sets whether `color` is transparent, leaving the other colors untouched.
+    pub fn palt_(&mut self, color: Color, transparent: bool) {
+        self.draw_data.palt_(color, transparent);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Palt>
+    ///
+    /// Sets the transparency of every color: bit `15 - c` makes color `c` transparent.
+    /// Returns the previous mask.
+    pub fn palt_mask(&mut self, mask: u16) -> u16 {
+        self.draw_data.palt_mask(mask)
+    }
+
+    /// Resets the draw and screen palettes, and transparency.
     pub fn reset_pal(&mut self) {
         self.draw_data.reset_pal();
     }
 
     pub fn pset(&mut self, x: i32, y: i32, color: Color) {
+        self.draw_data.color(color);
         self.draw_data.pset(x, y, color);
     }
 
+    /// Like [`Pico8::pset`], with the pen color (see [`Pico8::color`]).
+    pub fn pset_pen(&mut self, x: i32, y: i32) {
+        let color = self.draw_data.pen_color();
+        self.pset(x, y, color);
+    }
+
     /// <https://pico-8.fandom.com/wiki/Pget>
     pub fn pget(&self, x: i32, y: i32) -> Color {
         self.draw_data.pget(x, y)
@@ -92,10 +207,25 @@ impl Pico8 {
         self.draw_data.camera(x, y)
     }
 
-    pub fn clip(&mut self, _x: i32, _y: i32, _w: i32, _h: i32) {
-        todo!()
+    /// <https://pico-8.fandom.com/wiki/Clip>
+    ///
+    /// Returns the previous clipping rectangle, as `(x, y, w, h)`.
+    pub fn clip(&mut self, x: i32, y: i32, w: i32, h: i32) -> (i32, i32, i32, i32) {
+        self.draw_data.clip(x, y, w, h)
+    }
+
+    /// Pico8's `clip()` (with no arguments): resets the clipping rectangle to the whole screen.
+    ///
+    /// Returns the previous clipping rectangle, as `(x, y, w, h)`.
+    pub fn reset_clip(&mut self) -> (i32, i32, i32, i32) {
+        self.draw_data.reset_clip()
     }
 
+    /// <https://pico-8.fandom.com/wiki/Map>
+    ///
+    /// Draws `celw` by `celh` cells of the map, starting at cell (`cell_x`, `cell_y`),
+    /// at (`sx`, `sy`) on the screen. Sprite 0 is skipped.
+    /// A non-zero `layer` only draws sprites with all of those flags set.
     #[allow(clippy::too_many_arguments)]
     pub fn map(
         &mut self,
@@ -107,31 +237,118 @@ impl Pico8 {
         celh: i32,
         layer: u8,
     ) {
-        self.draw_data.map(
+        self.map_with(MapOptions {
             cell_x,
             cell_y,
-            sx,
-            sy,
-            celw,
-            celh,
+            screen_x: sx,
+            screen_y: sy,
+            cell_w: celw,
+            cell_h: celh,
             layer,
+        });
+    }
+
+    /// Like [`Pico8::map`], leaving out arguments through [`MapOptions::default`].
+    pub fn map_with(&mut self, options: MapOptions) {
+        self.draw_data.map(
+            options,
             &self.resources.map,
             &self.resources.sprite_flags,
             &self.resources.sprite_sheet,
         );
     }
 
-    pub fn spr(&mut self, spr: usize, x: i32, y: i32) {
-        let spr = self.resources.sprite_sheet.get_sprite(spr);
+    /// <https://pico-8.fandom.com/wiki/Tline>
+    ///
+    /// Draws a line from (`x0`, `y0`) to (`x1`, `y1`) textured with the map. The first pixel
+    /// takes its color from map coordinates (`mx`, `my`), in cells, and each following pixel
+    /// moves them by (`mdx`, `mdy`). Pico8's defaults are `mdx = 1.0 / 8.0`, `mdy = 0.0` and
+    /// `layers = 0`, which sample a pixel of the map per pixel of the line.
+    ///
+    /// Sprite 0 and sprites missing any flag of `layers` aren't drawn. The region of the map
+    /// that wraps is set with `poke(0x5f38, width)`, `poke(0x5f39, height)` (in cells, 0 for no
+    /// wrapping) and `poke(0x5f3a, x)`, `poke(0x5f3b, y)`, like in pico8.
+    #[allow(clippy::too_many_arguments)]
+    pub fn tline(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        mx: f32,
+        my: f32,
+        mdx: f32,
+        mdy: f32,
+        layers: u8,
+    ) {
+        self.draw_data.tline(
+            x0,
+            y0,
+            x1,
+            y1,
+            Fixed::from(mx),
+            Fixed::from(my),
+            Fixed::from(mdx),
+            Fixed::from(mdy),
+            layers,
+            &self.resources.map,
+            &self.resources.sprite_flags,
+            &self.resources.sprite_sheet,
+        );
+    }
 
-        self.draw_data.spr(spr, x, y);
+    pub fn spr(&mut self, spr: usize, x: i32, y: i32) {
+        self.draw_data.spr(&self.resources.sprite_sheet, spr, x, y);
     }
 
+    /// <https://pico-8.fandom.com/wiki/Spr>
+    ///
+    /// Draws `w` by `h` sprites (fractions allowed), flipping the whole block if requested.
     #[allow(clippy::too_many_arguments)]
     pub fn spr_(&mut self, spr: usize, x: i32, y: i32, w: f32, h: f32, flip_x: bool, flip_y: bool) {
-        let spr = self.resources.sprite_sheet.get_sprite(spr);
+        self.draw_data.spr_(
+            &self.resources.sprite_sheet,
+            spr,
+            x,
+            y,
+            w,
+            h,
+            flip_x,
+            flip_y,
+        );
+    }
 
-        self.draw_data.spr_(spr, x, y, w, h, flip_x, flip_y);
+    /// <https://pico-8.fandom.com/wiki/Sspr>
+    ///
+    /// Draws the `sw` by `sh` rectangle of the sprite sheet at (`sx`, `sy`),
+    /// stretched to `dw` by `dh` pixels at (`dx`, `dy`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn sspr(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        sw: i32,
+        sh: i32,
+        dx: i32,
+        dy: i32,
+        dw: i32,
+        dh: i32,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        self.draw_data.sspr(
+            &self.resources.sprite_sheet,
+            sx,
+            sy,
+            sw,
+            sh,
+            dx,
+            dy,
+            dw,
+            dh,
+            flip_x,
+            flip_y,
+        );
     }
 
     // TODO: Test
@@ -141,39 +358,383 @@ impl Pico8 {
         }
     }
 
-    pub fn fillp(&mut self) {
-        todo!()
+    /// <https://pico-8.fandom.com/wiki/Peek>
+    ///
+    /// Reads a byte of pico8's memory, see [`memory`](crate::memory) for its layout.
+    /// Addresses outside of it read as 0.
+    pub fn peek(&self, address: i32) -> u8 {
+        match usize::try_from(address) {
+            Ok(address) => self
+                .memory
+                .peek(address, &self.resources, &self.draw_data, &self.state),
+            Err(_) => 0,
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Poke>
+    ///
+    /// Writes a byte of pico8's memory. Writes outside of it are ignored.
+    pub fn poke(&mut self, address: i32, value: u8) {
+        if let Ok(address) = usize::try_from(address) {
+            self.memory.poke(
+                address,
+                value,
+                &mut self.resources,
+                &mut self.draw_data,
+                &mut self.state,
+            );
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Peek2>
+    ///
+    /// Reads a little endian 16 bit signed value.
+    pub fn peek2(&self, address: i32) -> i16 {
+        i16::from_le_bytes(self.peek_bytes(address))
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Poke2>
+    pub fn poke2(&mut self, address: i32, value: i16) {
+        self.poke_bytes(address, value.to_le_bytes());
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Peek4>
+    ///
+    /// Reads a little endian 32 bit value: the raw bits of a 16.16 fixed point number.
+    pub fn peek4(&self, address: i32) -> i32 {
+        i32::from_le_bytes(self.peek_bytes(address))
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Poke4>
+    pub fn poke4(&mut self, address: i32, value: i32) {
+        self.poke_bytes(address, value.to_le_bytes());
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Memcpy>
+    ///
+    /// Copies `len` bytes from `source` to `dest`. The ranges can overlap.
+    pub fn memcpy(&mut self, dest: i32, source: i32, len: i32) {
+        let bytes: Vec<u8> = (0..len).map(|i| self.peek(source + i)).collect();
+
+        for (i, byte) in (0..).zip(bytes) {
+            self.poke(dest + i, byte);
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Memset>
+    ///
+    /// Sets `len` bytes starting at `dest` to `value`.
+    pub fn memset(&mut self, dest: i32, value: u8, len: i32) {
+        for i in 0..len {
+            self.poke(dest + i, value);
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Fillp>
+    ///
+    /// Sets the 4x4 pattern used by `pset`, `line`, `rect`, `rectfill`, `circ`, `circfill` and `cls`.
+    /// Bits that are set in `pattern` are drawn with the secondary color (the high nibble of
+    /// the color argument), or not drawn at all if `transparent` is `true`.
+    ///
+    /// Pico8's `fillp()` (with no arguments) is `fillp(0, false)`.
+    pub fn fillp(&mut self, pattern: u16, transparent: bool) {
+        self.draw_data.fillp(pattern, transparent);
     }
 
     pub fn circ(&mut self, x: i32, y: i32, r: i32, color: Color) {
+        self.draw_data.color(color);
         self.draw_data.circ(x, y, r, color);
     }
+
+    /// Like [`Pico8::circ`], with the pen color (see [`Pico8::color`]).
+    pub fn circ_pen(&mut self, x: i32, y: i32, r: i32) {
+        let color = self.draw_data.pen_color();
+        self.circ(x, y, r, color);
+    }
+
     pub fn circfill(&mut self, x: i32, y: i32, r: i32, color: Color) {
+        self.draw_data.color(color);
         self.draw_data.circfill(x, y, r, color);
     }
 
+    /// Like [`Pico8::circfill`], with the pen color (see [`Pico8::color`]).
+    pub fn circfill_pen(&mut self, x: i32, y: i32, r: i32) {
+        let color = self.draw_data.pen_color();
+        self.circfill(x, y, r, color);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Oval>
+    ///
+    /// Draws the outline of the ellipse that fits in the rectangle from (`x0`, `y0`) to
+    /// (`x1`, `y1`).
+    pub fn oval(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.draw_data.color(color);
+        self.draw_data.oval(x0, y0, x1, y1, color);
+    }
+
+    /// Like [`Pico8::oval`], with the pen color (see [`Pico8::color`]).
+    pub fn oval_pen(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let color = self.draw_data.pen_color();
+        self.oval(x0, y0, x1, y1, color);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Ovalfill>
+    pub fn ovalfill(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.draw_data.color(color);
+        self.draw_data.ovalfill(x0, y0, x1, y1, color);
+    }
+
+    /// Like [`Pico8::ovalfill`], with the pen color (see [`Pico8::color`]).
+    pub fn ovalfill_pen(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let color = self.draw_data.pen_color();
+        self.ovalfill(x0, y0, x1, y1, color);
+    }
+
     pub fn rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.draw_data.color(color);
         self.draw_data.rect(x0, y0, x1, y1, color);
     }
 
+    /// Like [`Pico8::rect`], with the pen color (see [`Pico8::color`]).
+    pub fn rect_pen(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let color = self.draw_data.pen_color();
+        self.rect(x0, y0, x1, y1, color);
+    }
+
     pub fn rectfill(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.draw_data.color(color);
         self.draw_data.rectfill(x0, y0, x1, y1, color);
     }
 
+    /// Like [`Pico8::rectfill`], with the pen color (see [`Pico8::color`]).
+    pub fn rectfill_pen(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let color = self.draw_data.pen_color();
+        self.rectfill(x0, y0, x1, y1, color);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rrect>
+    ///
+    /// Draws the outline of a `width` by `height` rectangle at (`x`, `y`), with its corners
+    /// rounded with `radius` (at most half of the shortest side).
+    pub fn rrect(&mut self, x: i32, y: i32, width: i32, height: i32, radius: i32, color: Color) {
+        self.draw_data.color(color);
+        self.draw_data.rrect(x, y, width, height, radius, color);
+    }
+
+    /// Like [`Pico8::rrect`], with the pen color (see [`Pico8::color`]).
+    pub fn rrect_pen(&mut self, x: i32, y: i32, width: i32, height: i32, radius: i32) {
+        let color = self.draw_data.pen_color();
+        self.rrect(x, y, width, height, radius, color);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rrectfill>
+    pub fn rrectfill(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        radius: i32,
+        color: Color,
+    ) {
+        self.draw_data.color(color);
+        self.draw_data.rrectfill(x, y, width, height, radius, color);
+    }
+
+    /// Like [`Pico8::rrectfill`], with the pen color (see [`Pico8::color`]).
+    pub fn rrectfill_pen(&mut self, x: i32, y: i32, width: i32, height: i32, radius: i32) {
+        let color = self.draw_data.pen_color();
+        self.rrectfill(x, y, width, height, radius, color);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Line>
+    ///
+    /// Like every shape drawn with a color, sets the pen color. [`Pico8::line_to`] continues
+    /// from (`x1`, `y1`).
     pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        self.draw_data.color(color);
         self.draw_data.line(x0, y0, x1, y1, color);
+        self.draw_data.set_line_end(Some((x1, y1)));
+    }
+
+    /// Like [`Pico8::line`], with the pen color (see [`Pico8::color`]).
+    pub fn line_pen(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let color = self.draw_data.pen_color();
+        self.line(x0, y0, x1, y1, color);
+    }
+
+    /// Pico8's `line(x, y)`: draws a line with the pen color (see [`Pico8::color`]) from where
+    /// the last one ended. The first call after [`Pico8::line_reset`] only moves the end.
+    pub fn line_to(&mut self, x: i32, y: i32) {
+        self.draw_data.line_to(x, y);
+    }
+
+    /// Pico8's `line()`: the next [`Pico8::line_to`] starts a new line instead of
+    /// continuing the last one.
+    pub fn line_reset(&mut self) {
+        self.draw_data.set_line_end(None);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Color>
+    ///
+    /// Sets the pen color, which the `*_pen` variants of the shapes and `print` draw with, like
+    /// pico8's functions do when their color is omitted. Returns the previous one.
+    pub fn color(&mut self, color: Color) -> Color {
+        self.draw_data.color(color)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Print>
+    ///
+    /// Supports `\n`, `\t` and the control codes in [`font::control`](crate::font::control).
+    /// Sets the pen color and moves the text cursor below the printed text.
+    /// Returns the right-most x coordinate reached.
+    pub fn print(&mut self, text: &str, x: i32, y: i32, color: Color) -> i32 {
+        self.draw_data.print(text, x, y, color)
+    }
+
+    /// Like [`Pico8::print`], with the pen color (see [`Pico8::color`]).
+    pub fn print_pen(&mut self, text: &str, x: i32, y: i32) -> i32 {
+        let color = self.draw_data.pen_color();
+        self.print(text, x, y, color)
+    }
+
+    /// Like [`Pico8::print`], at the text cursor (see [`Pico8::cursor`]) and with the pen color.
+    pub fn print_at_cursor(&mut self, text: &str) -> i32 {
+        self.draw_data.print_at_cursor(text)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Cursor>
+    ///
+    /// Moves the text cursor, returns its previous position.
+    pub fn cursor(&mut self, x: i32, y: i32) -> (i32, i32) {
+        self.draw_data.cursor(x, y)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rnd>
+    ///
+    /// Random number from 0 (inclusive) to `limit` (exclusive), see [`Pico8::srand`].
+    pub fn rnd(&mut self, limit: f32) -> f32 {
+        self.rng.rnd(Fixed::from(limit)).into()
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Rnd>
+    ///
+    /// Random element of `elements`, like pico8's `rnd(table)`. `None` if it's empty.
+    pub fn rnd_from<'a, T>(&mut self, elements: &'a [T]) -> Option<&'a T> {
+        if elements.is_empty() {
+            None
+        } else {
+            elements.get(self.rng.index(elements.len()))
+        }
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Srand>
+    ///
+    /// Seeds the random number generator used by [`Pico8::rnd`], so that the numbers it
+    /// returns are the same every run, on every platform. It starts with a random seed.
+    pub fn srand(&mut self, seed: f32) {
+        self.rng.srand(Fixed::from(seed));
     }
 
-    pub fn print(&mut self, text: &str, x: i32, y: i32, color: Color) {
-        self.draw_data.print(text, x, y, color);
+    /// <https://pico-8.fandom.com/wiki/Cartdata>
+    ///
+    /// Makes [`Pico8::dset`] save its values for cart `id`, and loads the ones that were saved.
+    /// Returns whether there were saved values.
+    ///
+    /// `id` can have up to 64 lowercase letters, digits and underscores, and this can only be
+    /// called once.
+    pub fn cartdata(&mut self, id: &str) -> Result<bool, String> {
+        self.cart_data.open(&cartdata::directory(), id)
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Dget>
+    ///
+    /// Value saved in slot `index` (0 to 63).
+    pub fn dget(&self, index: i32) -> f32 {
+        self.cart_data.dget(index).into()
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Dset>
+    ///
+    /// Saves `value` in slot `index` (0 to 63), see [`Pico8::cartdata`].
+    pub fn dset(&mut self, index: i32, value: f32) {
+        if let Err(err) = self.cart_data.dset(index, Fixed::from(value)) {
+            log::error!("Couldn't save cartdata: {err}");
+        }
     }
 
     // audio
-    pub fn sfx(&mut self, _sound_id: u8) {
-        todo!()
+    /// <https://pico-8.fandom.com/wiki/Sfx>
+    ///
+    /// Plays sound effect `n` on the first free channel.
+    pub fn sfx(&mut self, n: i32) {
+        self.sfx_(n, -1, 0, Sfx::NOTE_COUNT as i32);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Sfx>
+    ///
+    /// - `n == -1` stops the sound on `channel` (or on every channel, if `channel == -1`).
+    /// - `n == -2` releases the loop of the sound on `channel` (or on every channel).
+    /// - `channel == -1` picks a free channel, `channel == -2` stops sound `n` wherever it's playing.
+    pub fn sfx_(&mut self, n: i32, channel: i32, offset: i32, length: i32) {
+        self.audio.sfx(n, channel, offset, length);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Music>
+    ///
+    /// Plays music starting at pattern `n`, or stops it if `n == -1`.
+    pub fn music(&mut self, n: i32) {
+        self.music_(n, 0, 0);
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Music>
+    ///
+    /// - `fade_len`: milliseconds to fade the music in (or out, when stopping it).
+    /// - `channel_mask`: channels reserved for music, `sfx` won't pick them if it can avoid it.
+    pub fn music_(&mut self, n: i32, fade_len: i32, channel_mask: u8) {
+        self.audio.music(n, fade_len, channel_mask);
     }
-    pub fn music(&mut self, _music_id: u8) {
-        todo!()
+
+    /// <https://pico-8.fandom.com/wiki/Time>
+    ///
+    /// Seconds since the game started. It advances by a fixed step every frame.
+    pub fn time(&self) -> f32 {
+        self.state.time()
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Stat>
+    ///
+    /// Supported stats:
+    /// - `1`: CPU usage, the fraction of the last frame's time budget spent in `update` and `draw`.
+    /// - `7`: Frames run during the last second.
+    /// - `8`: Target frame rate.
+    /// - `30`: Whether there are typed characters left to read with `stat(31)`.
+    /// - `31`: Next typed character, or `""` if there are none. Enter is `"\r"` and Backspace
+    ///   is `"\u{8}"`, like pico8's devkit mode.
+    /// - `32`, `33`: Mouse position.
+    /// - `34`: Pressed mouse buttons: bit 0 for the left button, 1 for the right one and 2 for
+    ///   the middle one.
+    /// - `36`: Scroll wheel steps during the last frame: positive up, negative down.
+    ///
+    /// Other stats are `Stat::Number(0.0)`.
+    pub fn stat(&mut self, n: i32) -> Stat {
+        match n {
+            1 => Stat::Number(self.state.cpu()),
+            7 => Stat::Number(self.state.fps() as f32),
+            8 => Stat::Number(self.state.target_fps() as f32),
+            30 => Stat::Bool(self.state.has_typed_char()),
+            31 => Stat::Text(
+                self.state
+                    .next_typed_char()
+                    .map(String::from)
+                    .unwrap_or_default(),
+            ),
+            32 => Stat::Number(self.state.mouse_x as f32),
+            33 => Stat::Number(self.state.mouse_y as f32),
+            34 => Stat::Number(self.state.mouse_buttons() as f32),
+            36 => Stat::Number(self.state.mouse_wheel() as f32),
+            _ => Stat::Number(0.0),
+        }
     }
 
     // Non-standard stuf
@@ -188,6 +749,13 @@ impl Pico8 {
     pub fn set_title(&mut self, new_title: String) {
         self.new_title = Some(new_title);
     }
+
+    /// Sets where the sound is played.
+    ///
+    /// Without an output, sound effects still advance, but nothing is heard.
+    pub fn set_audio_output(&mut self, audio_output: Box<dyn AudioOutput>) {
+        self.audio_output = Some(audio_output);
+    }
 }
 
 // Utility pub(crate) methods
@@ -203,12 +771,54 @@ impl Pico8 {
         flip_x: bool,
         flip_y: bool,
     ) {
-        self.draw_data.spr_(sprite, x, y, w, h, flip_x, flip_y);
+        self.draw_data.raw_spr(sprite, x, y, w, h, flip_x, flip_y);
+    }
+
+    fn peek_bytes<const N: usize>(&self, address: i32) -> [u8; N] {
+        let mut bytes = [0; N];
+        for (i, byte) in (0..).zip(bytes.iter_mut()) {
+            *byte = self.peek(address + i);
+        }
+
+        bytes
+    }
+
+    fn poke_bytes<const N: usize>(&mut self, address: i32, bytes: [u8; N]) {
+        for (i, byte) in (0..).zip(bytes) {
+            self.poke(address + i, byte);
+        }
     }
 
     pub fn take_new_title(&mut self) -> Option<String> {
         self.new_title.take()
     }
+
+    /// Advances the sound by `millis` milliseconds, writing it to the audio output (if any).
+    ///
+    /// Frames rarely last a whole number of samples, so what's left over is rendered in
+    /// later frames to keep the sound in time.
+    pub fn render_audio(&mut self, millis: f64) {
+        let samples = audio::SAMPLE_RATE as f64 * millis / 1000.0 + self.audio_leftover;
+        let len = samples.floor();
+        self.audio_leftover = samples - len;
+        let len = len as usize;
+        let sound_effects = &self.resources.sound_effects;
+        let music = &self.resources.music;
+
+        let result = match &mut self.audio_output {
+            Some(output) => self
+                .audio
+                .render_to(sound_effects, music, len, output.as_mut()),
+            None => {
+                self.audio.render(sound_effects, music, &mut vec![0.0; len]);
+                Ok(())
+            }
+        };
+
+        if let Err(err) = result {
+            log::error!("Couldn't write audio: {err}");
+        }
+    }
 }
 
 // Top level functions that pico8 provides that don't modify the global state.
@@ -220,6 +830,9 @@ pub fn sin(f: f32) -> f32 {
 }
 
 /// Pico8's [`rnd`](<https://pico-8.fandom.com/wiki/Rnd>) function.
+///
+/// It isn't affected by [`Pico8::srand`], use [`Pico8::rnd`] for reproducible numbers.
+#[deprecated(note = "use `Pico8::rnd`, which `Pico8::srand` seeds")]
 pub fn rnd(limit: f32) -> f32 {
     rand::thread_rng().gen_range(0.0..limit)
 }
@@ -237,9 +850,119 @@ pub fn flr(num: f32) -> i32 {
     num.floor() as i32
 }
 
+/// Pico8's [`cos`](<https://pico-8.fandom.com/wiki/Cos>) function.
+pub fn cos(f: f32) -> f32 {
+    (f * 2.0 * PI).cos()
+}
+
+/// Pico8's [`atan2`](<https://pico-8.fandom.com/wiki/Atan2>) function.
+///
+/// Angle of `(dx, dy)` in turns, from 0 to 1. Like [`sin`], y is inverted:
+/// `atan2(0.0, -1.0) == 0.25`.
+pub fn atan2(dx: f32, dy: f32) -> f32 {
+    if dx == 0.0 && dy == 0.0 {
+        return 0.25;
+    }
+
+    ((-dy).atan2(dx) / (2.0 * PI)).rem_euclid(1.0)
+}
+
+/// Pico8's [`sqrt`](<https://pico-8.fandom.com/wiki/Sqrt>) function. Negative numbers give 0.
+pub fn sqrt(num: f32) -> f32 {
+    if num > 0.0 {
+        num.sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Pico8's [`abs`](<https://pico-8.fandom.com/wiki/Abs>) function.
+pub fn abs(num: f32) -> f32 {
+    num.abs()
+}
+
+/// Pico8's [`sgn`](<https://pico-8.fandom.com/wiki/Sgn>) function: -1 for negative numbers, 1 otherwise (0 included).
+pub fn sgn(num: f32) -> f32 {
+    if num < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+/// Pico8's [`ceil`](<https://pico-8.fandom.com/wiki/Ceil>) function.
+pub fn ceil(num: f32) -> i32 {
+    num.ceil() as i32
+}
+
+/// Pico8's [`min`](<https://pico-8.fandom.com/wiki/Min>) function.
+pub fn min(first: f32, second: f32) -> f32 {
+    first.min(second)
+}
+
+/// Pico8's [`max`](<https://pico-8.fandom.com/wiki/Max>) function.
+pub fn max(first: f32, second: f32) -> f32 {
+    first.max(second)
+}
+
+// Bitwise operations work on the 16.16 fixed point representation of the numbers.
+
+fn bitwise(first: f32, second: f32, op: impl Fn(Fixed, Fixed) -> Fixed) -> f32 {
+    op(Fixed::from(first), Fixed::from(second)).into()
+}
+
+/// Pico8's [`band`](<https://pico-8.fandom.com/wiki/Band>) function.
+pub fn band(first: f32, second: f32) -> f32 {
+    bitwise(first, second, |a, b| a & b)
+}
+
+/// Pico8's [`bor`](<https://pico-8.fandom.com/wiki/Bor>) function.
+pub fn bor(first: f32, second: f32) -> f32 {
+    bitwise(first, second, |a, b| a | b)
+}
+
+/// Pico8's [`bxor`](<https://pico-8.fandom.com/wiki/Bxor>) function.
+pub fn bxor(first: f32, second: f32) -> f32 {
+    bitwise(first, second, |a, b| a ^ b)
+}
+
+/// Pico8's [`bnot`](<https://pico-8.fandom.com/wiki/Bnot>) function.
+pub fn bnot(num: f32) -> f32 {
+    (!Fixed::from(num)).into()
+}
+
+/// Pico8's [`shl`](<https://pico-8.fandom.com/wiki/Shl>) function.
+pub fn shl(num: f32, bits: i32) -> f32 {
+    (Fixed::from(num) << bits).into()
+}
+
+/// Pico8's [`shr`](<https://pico-8.fandom.com/wiki/Shr>) function (arithmetic shift, keeps the sign).
+pub fn shr(num: f32, bits: i32) -> f32 {
+    (Fixed::from(num) >> bits).into()
+}
+
+/// Pico8's [`lshr`](<https://pico-8.fandom.com/wiki/Lshr>) function (logical shift, fills with zeros).
+pub fn lshr(num: f32, bits: i32) -> f32 {
+    Fixed::from(num).lshr(bits).into()
+}
+
+/// Pico8's [`rotl`](<https://pico-8.fandom.com/wiki/Rotl>) function.
+pub fn rotl(num: f32, bits: i32) -> f32 {
+    Fixed::from(num).rotl(bits).into()
+}
+
+/// Pico8's [`rotr`](<https://pico-8.fandom.com/wiki/Rotr>) function.
+pub fn rotr(num: f32, bits: i32) -> f32 {
+    Fixed::from(num).rotr(bits).into()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{flr, mid, rnd, sin};
+    use super::{
+        abs, atan2, band, bnot, bor, bxor, ceil, cos, flr, lshr, max, mid, min, rotl, rotr, sgn,
+        shl, shr, sin, sqrt, Stat,
+    };
+    use std::f32::consts::{FRAC_1_SQRT_2, SQRT_2};
 
     macro_rules! assert_delta {
         ($x:expr, $y:expr, $d:expr) => {
@@ -263,9 +986,10 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn rnd_works() {
         for _ in 0..100 {
-            let random_value = rnd(50.0);
+            let random_value = super::rnd(50.0);
 
             assert!(0.0 < random_value && random_value < 50.0);
         }
@@ -285,4 +1009,165 @@ mod tests {
         assert_eq!(flr(7.0), 7);
         assert_eq!(flr(-7.0), -7);
     }
+
+    #[test]
+    fn math_functions_match_pico8() {
+        // (expression, result, value printed by pico8)
+        let cases = [
+            ("cos(0)", cos(0.0), 1.0),
+            ("cos(0.25)", cos(0.25), 0.0),
+            ("cos(0.5)", cos(0.5), -1.0),
+            ("cos(0.125)", cos(0.125), FRAC_1_SQRT_2),
+            ("atan2(1, 0)", atan2(1.0, 0.0), 0.0),
+            ("atan2(0, -1)", atan2(0.0, -1.0), 0.25),
+            ("atan2(-1, 0)", atan2(-1.0, 0.0), 0.5),
+            ("atan2(0, 1)", atan2(0.0, 1.0), 0.75),
+            ("atan2(1, 1)", atan2(1.0, 1.0), 0.875),
+            ("atan2(-1, -1)", atan2(-1.0, -1.0), 0.375),
+            ("atan2(0, 0)", atan2(0.0, 0.0), 0.25),
+            ("sqrt(16)", sqrt(16.0), 4.0),
+            ("sqrt(2)", sqrt(2.0), SQRT_2),
+            ("sqrt(-1)", sqrt(-1.0), 0.0),
+            ("abs(-3.5)", abs(-3.5), 3.5),
+            ("sgn(-2)", sgn(-2.0), -1.0),
+            ("sgn(0)", sgn(0.0), 1.0),
+            ("sgn(0.5)", sgn(0.5), 1.0),
+            ("ceil(1.2)", ceil(1.2) as f32, 2.0),
+            ("ceil(-1.2)", ceil(-1.2) as f32, -1.0),
+            ("ceil(3)", ceil(3.0) as f32, 3.0),
+            ("min(1, -2)", min(1.0, -2.0), -2.0),
+            ("max(1, -2)", max(1.0, -2.0), 1.0),
+            ("band(5.5, 3)", band(5.5, 3.0), 1.0),
+            ("band(0.75, 0.5)", band(0.75, 0.5), 0.5),
+            ("bor(4, 0.5)", bor(4.0, 0.5), 4.5),
+            ("bor(-1, 1)", bor(-1.0, 1.0), -1.0),
+            ("bxor(6, 3)", bxor(6.0, 3.0), 5.0),
+            ("bxor(-1, 0x0.ffff)", bxor(-1.0, 0.99998474), -0.0000153),
+            ("bnot(0)", bnot(0.0), -0.0000153),
+            ("bnot(1)", bnot(1.0), -1.0000153),
+            ("shl(1, 4)", shl(1.0, 4), 16.0),
+            ("shl(1, -1)", shl(1.0, -1), 0.5),
+            ("shl(0x4000, 1)", shl(16384.0, 1), -32768.0),
+            ("shr(8, 2)", shr(8.0, 2), 2.0),
+            ("shr(-8, 2)", shr(-8.0, 2), -2.0),
+            ("shr(1, 1)", shr(1.0, 1), 0.5),
+            ("lshr(-8, 2)", lshr(-8.0, 2), 16382.0),
+            ("lshr(-1, 16)", lshr(-1.0, 16), 0.9999847),
+            ("rotl(1, 16)", rotl(1.0, 16), 0.0000153),
+            ("rotl(0x8000, 1)", rotl(-32768.0, 1), 0.0000153),
+            ("rotr(1, 1)", rotr(1.0, 1), 0.5),
+            ("rotr(0x0.0001, 1)", rotr(0.0000153, 1), -32768.0),
+        ];
+
+        for (expression, result, expected) in cases {
+            assert!(
+                (result - expected).abs() < 0.0001,
+                "{expression} was {result}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn btn_bitfield_has_a_byte_per_player() {
+        use crate::{Input, InputEvent, Key, KeyState, KeyboardEvent, Pico8, Resources};
+
+        let mut pico8 = Pico8::new(Resources::empty());
+        let mut input = Input::new();
+        for key in [Key::UpArrow, Key::X, Key::S, Key::Shift] {
+            input.on_event(InputEvent::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            }));
+        }
+        pico8.state.update_input(&mut input);
+
+        assert_eq!(pico8.btn_bitfield(), 0b01_0001_0010_0100);
+        assert_eq!(pico8.btnp_bitfield(), 0b01_0001_0010_0100);
+
+        pico8.state.update_input(&mut input);
+        assert_eq!(pico8.btn_bitfield(), 0b01_0001_0010_0100);
+        assert_eq!(pico8.btnp_bitfield(), 0);
+    }
+
+    #[test]
+    fn stat_polls_typed_text() {
+        use crate::{Input, Pico8, Resources};
+
+        let mut pico8 = Pico8::new(Resources::empty());
+        let mut input = Input::new();
+        input.on_text('o');
+        input.on_text('k');
+        input.on_text('\r');
+        pico8.state.update_input(&mut input);
+
+        let mut typed = String::new();
+        while pico8.stat(30) == Stat::Bool(true) {
+            let Stat::Text(text) = pico8.stat(31) else {
+                panic!("stat(31) should be text");
+            };
+            typed.push_str(&text);
+        }
+
+        assert_eq!(typed, "ok\r");
+        assert_eq!(pico8.stat(31), Stat::Text(String::new()));
+    }
+
+    #[test]
+    fn the_bottom_half_of_the_map_is_shared_with_the_sprite_sheet() {
+        use crate::{Pico8, Resources};
+
+        let mut pico8 = Pico8::new(Resources::empty());
+        // Cell (1, 32) is byte 1 of sprite 128's top row: pixels 2 and 3.
+        pico8.mset(1, 32, 0x5a);
+        let sprite_sheet = &pico8.resources.sprite_sheet;
+        assert_eq!(
+            [sprite_sheet.get(2, 64), sprite_sheet.get(3, 64)],
+            [Some(0xa), Some(0x5)]
+        );
+
+        pico8.sset(127, 127, 0xc);
+        assert_eq!(pico8.mget(127, 63), 0xc0);
+
+        pico8.mset(0, 31, 1);
+        assert_eq!(
+            pico8.resources.sprite_sheet.get(0, 64),
+            Some(0),
+            "the top half isn't shared"
+        );
+    }
+
+    #[test]
+    fn shapes_set_the_pen_color_used_by_line_to() {
+        use crate::{colors, Pico8, Resources};
+
+        let mut pico8 = Pico8::new(Resources::empty());
+        pico8.line(0, 0, 4, 0, colors::RED);
+        pico8.line_to(4, 4);
+        assert_eq!(pico8.pget(4, 2), colors::RED);
+
+        pico8.ovalfill(10, 10, 14, 12, colors::GREEN);
+        assert_eq!(pico8.color(colors::BLUE), colors::GREEN);
+        pico8.line_to(0, 4);
+        assert_eq!(pico8.pget(2, 4), colors::BLUE);
+
+        pico8.line_reset();
+        pico8.line_to(0, 10);
+        assert_eq!(pico8.pget(0, 7), colors::BLACK);
+    }
+
+    #[test]
+    fn pen_variants_draw_with_the_pen_color() {
+        use crate::{colors, Pico8, Resources};
+
+        let mut pico8 = Pico8::new(Resources::empty());
+        pico8.color(colors::RED);
+        pico8.rectfill_pen(0, 0, 2, 2);
+        pico8.circ_pen(20, 20, 3);
+        pico8.rrect(40, 40, 8, 8, 2, colors::GREEN);
+        pico8.pset_pen(60, 60);
+
+        assert_eq!(pico8.pget(1, 1), colors::RED);
+        assert_eq!(pico8.pget(23, 20), colors::RED);
+        assert_eq!(pico8.pget(60, 60), colors::GREEN);
+    }
 }
@@ -0,0 +1,149 @@
+use crate::Fixed;
+
+/// Pico8's random number generator, see <https://pico-8.fandom.com/wiki/Rnd>.
+///
+/// It only uses integer arithmetic, so a seed gives the same sequence on every platform.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    hi: u32,
+    lo: u32,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: Fixed) -> Self {
+        let mut rng = Self { hi: 0, lo: 0 };
+        rng.srand(seed);
+
+        rng
+    }
+
+    /// <https://pico-8.fandom.com/wiki/Srand>
+    pub(crate) fn srand(&mut self, seed: Fixed) {
+        let seed = seed.to_bits() as u32;
+
+        if seed == 0 {
+            self.hi = 0x6000_9755;
+            self.lo = 0xdead_beef;
+        } else {
+            self.hi = seed ^ 0xbead_29ba;
+            self.lo = seed;
+        }
+
+        // Mix the seed in, so that close seeds give unrelated sequences.
+        for _ in 0..32 {
+            self.next();
+        }
+    }
+
+    fn next(&mut self) -> u32 {
+        self.hi = self.hi.rotate_left(16).wrapping_add(self.lo);
+        self.lo = self.lo.wrapping_add(self.hi);
+
+        self.hi
+    }
+
+    /// Random number from 0 (inclusive) to `limit` (exclusive). `rnd(0)` is 0.
+    pub(crate) fn rnd(&mut self, limit: Fixed) -> Fixed {
+        let random = self.next();
+        let limit = limit.to_bits() as u32;
+
+        if limit == 0 {
+            Fixed::ZERO
+        } else {
+            Fixed::from_bits((random % limit) as i32)
+        }
+    }
+
+    /// Random index into a slice of length `len`, which must not be 0.
+    ///
+    /// Same as `flr(rnd(len))` for the lengths pico8 numbers can hold (below 32768).
+    /// Longer slices use a second random number, so that every index can come up.
+    pub(crate) fn index(&mut self, len: usize) -> usize {
+        if len < 0x8000 {
+            let limit = (len as u32) << Fixed::FRACTION_BITS;
+
+            ((self.next() % limit) >> Fixed::FRACTION_BITS) as usize
+        } else {
+            let random = (self.next() as u64) << 32 | self.next() as u64;
+
+            (random % len as u64) as usize
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+    use crate::Fixed;
+
+    fn sequence(rng: &mut Rng) -> Vec<i32> {
+        (0..8)
+            .map(|_| rng.rnd(Fixed::from(100)).to_bits())
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_sequence() {
+        let mut first = Rng::new(Fixed::from(42));
+        let mut second = Rng::new(Fixed::from(7));
+        second.srand(Fixed::from(42));
+
+        assert_eq!(sequence(&mut first), sequence(&mut second));
+    }
+
+    #[test]
+    fn different_seeds_give_different_sequences() {
+        let first = sequence(&mut Rng::new(Fixed::from(1)));
+        let second = sequence(&mut Rng::new(Fixed::from(2)));
+        let zero = sequence(&mut Rng::new(Fixed::ZERO));
+
+        assert_ne!(first, second);
+        assert_ne!(first, zero);
+    }
+
+    #[test]
+    fn sequences_dont_depend_on_the_platform() {
+        let mut rng = Rng::new(Fixed::from(1));
+
+        assert_eq!(
+            sequence(&mut rng),
+            [1774775, 4062488, 5019618, 495695, 4953117, 5860570, 1797718, 2284254]
+        );
+    }
+
+    #[test]
+    fn rnd_stays_in_range() {
+        let mut rng = Rng::new(Fixed::from(3));
+        let limit = Fixed::from(0.5);
+
+        for _ in 0..1000 {
+            let n = rng.rnd(limit);
+
+            assert!(Fixed::ZERO <= n && n < limit, "{n:?}");
+        }
+        assert_eq!(rng.rnd(Fixed::ZERO), Fixed::ZERO);
+    }
+
+    #[test]
+    fn index_reaches_every_element() {
+        let mut rng = Rng::new(Fixed::from(3));
+        let mut seen = [false; 5];
+
+        for _ in 0..100 {
+            seen[rng.index(seen.len())] = true;
+        }
+
+        assert_eq!(seen, [true; 5]);
+    }
+
+    #[test]
+    fn index_reaches_the_end_of_long_slices() {
+        let mut rng = Rng::new(Fixed::from(3));
+        let len = 200_000;
+
+        let indices: Vec<usize> = (0..100).map(|_| rng.index(len)).collect();
+
+        assert!(indices.iter().all(|&index| index < len));
+        assert!(indices.iter().any(|&index| index >= 0x10000));
+    }
+}
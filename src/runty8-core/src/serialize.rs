@@ -1,6 +1,6 @@
 use itertools::Itertools;
 
-use crate::draw_data::COLORS;
+use crate::draw_data::{get_color, ORIGINAL_PALETTE};
 use crate::map::Map;
 use crate::sprite_sheet::SpriteSheet;
 use std::fmt::Display;
@@ -12,14 +12,14 @@ fn write_and_log(file_name: &str, contents: &str) {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-type WriteError = std::io::Error;
+pub(crate) type WriteError = std::io::Error;
 #[cfg(target_arch = "wasm32")]
-type WriteError = wasm::Error;
+pub(crate) type WriteError = wasm::Error;
 
 /// Stores this file:
 ///  - Native: Uses regular `std::fs::write`
 ///  - Web: Uses `localStorage.setItem`
-fn write(file_name: &str, contents: &str) -> Result<(), WriteError> {
+pub(crate) fn write(file_name: &str, contents: &str) -> Result<(), WriteError> {
     #[cfg(not(target_arch = "wasm32"))]
     return std::fs::write(file_name, contents);
     #[cfg(target_arch = "wasm32")]
@@ -28,7 +28,7 @@ fn write(file_name: &str, contents: &str) -> Result<(), WriteError> {
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
-    pub(super) type Error = wasm_bindgen::JsValue;
+    pub(crate) type Error = wasm_bindgen::JsValue;
 
     pub(super) fn write(key: &str, contents: &str) -> Result<(), Error> {
         let storage = web_sys::window()
@@ -67,7 +67,7 @@ struct Color {
 
 impl Color {
     fn from_pico8(color_index: u8) -> Self {
-        let c = COLORS[color_index as usize];
+        let c = get_color(color_index);
         let r = ((c >> 16) & 0x0000FF) as u8;
         let g = ((c >> 8) & 0x0000FF) as u8;
         let b = (c & 0x0000FF) as u8;
@@ -97,12 +97,23 @@ pub struct Ppm {
 impl Ppm {
     #[allow(dead_code)]
     pub fn from_map(map: &Map, sprite_sheet: &SpriteSheet) -> Self {
+        Self::from_map_with_palette(map, sprite_sheet, &ORIGINAL_PALETTE)
+    }
+
+    /// Like [`Ppm::from_map`], showing each color `c` as `palette[c]`,
+    /// which can be one of the extended colors (128 to 143).
+    pub fn from_map_with_palette(
+        map: &Map,
+        sprite_sheet: &SpriteSheet,
+        palette: &[u8; 16],
+    ) -> Self {
         let width = 1024;
         let height = 4 * 16 * 8;
         let mut data = vec![Color { r: 0, g: 0, b: 0 }; width * height];
 
-        for (y, row) in map.map.iter().chunks(128).into_iter().enumerate() {
-            for (x, sprite_id) in row.into_iter().copied().enumerate() {
+        for y in 0..Map::HEIGHT_SPRITES {
+            for x in 0..Map::WIDTH_SPRITES {
+                let sprite_id = map.mget(sprite_sheet, x as i32, y as i32);
                 let real_x = x * 8;
                 let real_y = y * 8;
 
@@ -112,7 +123,7 @@ impl Ppm {
                     let offset_x = pixel_index % 8;
                     let offset_y = pixel_index / 8;
 
-                    let color = Color::from_pico8(pixel);
+                    let color = Color::from_pico8(palette[pixel as usize & 0x0f]);
 
                     data[(real_x + offset_x) + (real_y + offset_y) * width] = color;
                 }
@@ -128,6 +139,12 @@ impl Ppm {
 
     #[allow(dead_code)]
     pub fn from_sprite_sheet(sprite_sheet: &SpriteSheet) -> Self {
+        Self::from_sprite_sheet_with_palette(sprite_sheet, &ORIGINAL_PALETTE)
+    }
+
+    /// Like [`Ppm::from_sprite_sheet`], showing each color `c` as `palette[c]`,
+    /// which can be one of the extended colors (128 to 143).
+    pub fn from_sprite_sheet_with_palette(sprite_sheet: &SpriteSheet, palette: &[u8; 16]) -> Self {
         let sprite_sheet = &sprite_sheet.sprite_sheet;
         let width = SpriteSheet::SPRITES_PER_ROW * SPRITE_WIDTH;
         let height = SPRITE_PAGES * ROWS_PER_PAGE * SPRITE_WIDTH;
@@ -152,7 +169,7 @@ impl Ppm {
                 let x = base_x + pixel_index % SPRITE_WIDTH;
                 let y = base_y + pixel_index / SPRITE_WIDTH;
 
-                let color = Color::from_pico8(c);
+                let color = Color::from_pico8(palette[c as usize & 0x0f]);
                 data[x + y * 128] = color;
             }
         }
@@ -185,3 +202,27 @@ pub struct Serialized {
     pub file_name: String,
     pub serialized: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_can_use_extended_colors() {
+        let mut sprite_sheet = SpriteSheet::new();
+        sprite_sheet.set(1, 0, 8);
+        let mut palette = ORIGINAL_PALETTE;
+        palette[8] = 136;
+
+        let ppm = Ppm::from_sprite_sheet_with_palette(&sprite_sheet, &palette).serialize();
+        let mut lines = ppm.lines();
+
+        assert_eq!(lines.next(), Some("P3"));
+        assert_eq!(lines.next(), Some("128 128"));
+        assert_eq!(lines.next(), Some("255"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(" 0 0 0  190 18 80  0 0 0 "));
+    }
+}
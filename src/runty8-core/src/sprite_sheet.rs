@@ -18,12 +18,13 @@ impl SpriteSheet {
 impl SpriteSheet {
     pub const SPRITES_PER_ROW: usize = 16;
 
-    /// This is kind of a lie.
-    /// The pico8 sprite sheet supports 128 "real" sprites
-    /// The other 128 share memory with the map,
-    /// and will override its data if used
+    /// Like in pico8, sprites 128 to 255 share memory with the bottom half of the map
+    /// (rows 32 to 63), so drawing them changes the map and vice versa.
     pub const SPRITE_COUNT: usize = 256;
 
+    /// Bytes in pico8's layout of the sprite sheet: 64 per row of pixels.
+    const BYTES_PER_ROW: usize = Self::SPRITES_PER_ROW * Sprite::WIDTH / 2;
+
     pub fn new() -> Self {
         Self {
             sprite_sheet: vec![0; Self::SPRITE_COUNT * Sprite::WIDTH * Sprite::HEIGHT],
@@ -49,6 +50,40 @@ impl SpriteSheet {
         self.sprite_sheet[Self::to_linear_index(x, y)] = c;
     }
 
+    /// Color of the pixel at coordinate (x,y) in the spritesheet, if it's inside it.
+    pub fn get(&self, x: usize, y: usize) -> Option<Color> {
+        if x < Self::SPRITES_PER_ROW * Sprite::WIDTH {
+            self.sprite_sheet.get(Self::to_linear_index(x, y)).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Byte `offset` of the sprite sheet in pico8's memory layout, where each byte has 2 pixels
+    /// (the left one in the low nibble). 0 if it's outside the sprite sheet.
+    pub(crate) fn peek(&self, offset: usize) -> u8 {
+        let (x, y) = Self::byte_location(offset);
+        let color = |x| self.get(x, y).unwrap_or(0);
+
+        color(x) | color(x + 1) << 4
+    }
+
+    /// Writes byte `offset` of the sprite sheet, see [`SpriteSheet::peek`].
+    pub(crate) fn poke(&mut self, offset: usize, value: u8) {
+        let (x, y) = Self::byte_location(offset);
+
+        self.set(x, y, value & 0x0f);
+        self.set(x + 1, y, value >> 4);
+    }
+
+    /// Coordinates of the left pixel stored in byte `offset`.
+    fn byte_location(offset: usize) -> (usize, usize) {
+        (
+            2 * (offset % Self::BYTES_PER_ROW),
+            offset / Self::BYTES_PER_ROW,
+        )
+    }
+
     pub fn to_linear_index(x: usize, y: usize) -> usize {
         let x_part = 64 * (x / 8) + x % 8;
         let y_part = 16 * 64 * (y / 8) + 8 * (y % 8);
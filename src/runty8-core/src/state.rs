@@ -1,33 +1,56 @@
+use std::collections::VecDeque;
+
 use crate::input::Input;
-use crate::Button;
+use crate::{Button, MAX_PLAYERS};
 use ButtonState::*;
 
 #[derive(Debug)]
 pub struct State {
-    left: ButtonState,
-    right: ButtonState,
-    up: ButtonState,
-    down: ButtonState,
-    x: ButtonState,
-    c: ButtonState,
+    /// Each player's buttons, in the order of [`Button::PLAYER_BUTTONS`].
+    buttons: [[ButtonState; Button::PLAYER_BUTTONS.len()]; MAX_PLAYERS],
     pub mouse_x: i32,
     pub mouse_y: i32,
     mouse_pressed: ButtonState,
+    right_mouse_pressed: ButtonState,
+    middle_mouse_pressed: ButtonState,
+    /// Scroll wheel steps during the last frame.
+    wheel: i32,
+    /// Game time, advanced by a fixed step every frame.
+    time_millis: f64,
+    target_fps: u32,
+    /// Frames run during the last second of real time.
+    fps: u32,
+    frames_this_second: u32,
+    millis_this_second: f64,
+    /// Time spent in `update` and `draw` during the current frame.
+    busy_millis: f64,
+    /// Fraction of the last frame's time budget spent in `update` and `draw`.
+    cpu: f32,
+    /// Characters typed and not read yet.
+    typed: VecDeque<char>,
+    btnp_repeat: BtnpRepeat,
 }
 
 #[allow(clippy::new_without_default)]
 impl State {
     pub fn new() -> Self {
         Self {
-            left: NotPressed,
-            right: NotPressed,
-            up: NotPressed,
-            down: NotPressed,
-            x: NotPressed,
-            c: NotPressed,
+            buttons: [[NotPressed; Button::PLAYER_BUTTONS.len()]; MAX_PLAYERS],
             mouse_x: 64,
             mouse_y: 64,
             mouse_pressed: NotPressed,
+            right_mouse_pressed: NotPressed,
+            middle_mouse_pressed: NotPressed,
+            wheel: 0,
+            time_millis: 0.0,
+            target_fps: 30,
+            fps: 30,
+            frames_this_second: 0,
+            millis_this_second: 0.0,
+            busy_millis: 0.0,
+            cpu: 0.0,
+            typed: VecDeque::new(),
+            btnp_repeat: BtnpRepeat::default(),
         }
     }
 
@@ -36,72 +59,211 @@ impl State {
         self.mouse_y = mouse_y;
     }
 
-    pub fn update_input(&mut self, input: &Input) {
-        self.left.update(input.left);
-        self.right.update(input.right);
-        self.up.update(input.up);
-        self.down.update(input.down);
-        self.x.update(input.x);
-        self.c.update(input.c);
-        self.mouse_pressed.update(input.mouse);
+    pub fn update_input(&mut self, input: &mut Input) {
+        for (buttons, input) in self.buttons.iter_mut().zip(input.buttons) {
+            for (button, is_pressed) in buttons.iter_mut().zip(input) {
+                button.update(is_pressed, self.btnp_repeat);
+            }
+        }
+        // The repeat registers are for the player buttons, held mouse buttons don't repeat.
+        self.mouse_pressed.update(input.mouse, BtnpRepeat::OFF);
+        self.right_mouse_pressed
+            .update(input.right_mouse, BtnpRepeat::OFF);
+        self.middle_mouse_pressed
+            .update(input.middle_mouse, BtnpRepeat::OFF);
+        self.wheel = std::mem::take(&mut input.wheel);
         self.mouse_x = input.mouse_x;
         self.mouse_y = input.mouse_y;
+        self.typed.extend(input.typed.drain(..));
+    }
+
+    /// Frame rate the game runs at, see [`App::FPS`](crate::App::FPS).
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.target_fps = fps;
+        self.fps = fps;
     }
 
-    pub(crate) fn button(&self, button: Button) -> &ButtonState {
-        match button {
-            Button::Left => &self.left,
-            Button::Right => &self.right,
-            Button::Up => &self.up,
-            Button::Down => &self.down,
-            Button::Cross => &self.x,
-            Button::Circle => &self.c,
-            Button::Mouse => &self.mouse_pressed,
+    /// Registers real time passing, to measure the frame rate.
+    pub fn on_tick(&mut self, delta_millis: f64) {
+        self.millis_this_second += delta_millis;
+
+        if self.millis_this_second >= 1000.0 {
+            self.fps =
+                (self.frames_this_second as f64 * 1000.0 / self.millis_this_second).round() as u32;
+            self.frames_this_second = 0;
+            self.millis_this_second = 0.0;
+        }
+    }
+
+    /// Registers time spent running the game's `update` or `draw` in the current frame.
+    pub fn add_busy_time(&mut self, millis: f64) {
+        self.busy_millis += millis;
+    }
+
+    /// Ends a frame, advancing the game time by `step_millis`.
+    pub fn advance_time(&mut self, step_millis: f64) {
+        self.time_millis += step_millis;
+        self.frames_this_second += 1;
+        self.cpu = (self.busy_millis / step_millis) as f32;
+        self.busy_millis = 0.0;
+    }
+
+    /// Seconds since the game started.
+    pub(crate) fn time(&self) -> f32 {
+        (self.time_millis / 1000.0) as f32
+    }
+
+    pub(crate) fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub(crate) fn target_fps(&self) -> u32 {
+        self.target_fps
+    }
+
+    pub(crate) fn cpu(&self) -> f32 {
+        self.cpu
+    }
+
+    /// Bitfield of the pressed mouse buttons, like pico8's `stat(34)`:
+    /// bit 0 for the left button, 1 for the right one and 2 for the middle one.
+    pub(crate) fn mouse_buttons(&self) -> u8 {
+        [
+            self.mouse_pressed,
+            self.right_mouse_pressed,
+            self.middle_mouse_pressed,
+        ]
+        .iter()
+        .enumerate()
+        .map(|(bit, button)| (button.btn() as u8) << bit)
+        .sum()
+    }
+
+    /// Scroll wheel steps during the last frame, like pico8's `stat(36)`.
+    pub(crate) fn mouse_wheel(&self) -> i32 {
+        self.wheel
+    }
+
+    pub(crate) fn has_typed_char(&self) -> bool {
+        !self.typed.is_empty()
+    }
+
+    pub(crate) fn next_typed_char(&mut self) -> Option<char> {
+        self.typed.pop_front()
+    }
+
+    /// State of `player`'s `button`. Only player 0 has [`Button::Mouse`].
+    pub(crate) fn button(&self, button: Button, player: usize) -> ButtonState {
+        match button.index() {
+            Some(index) => self
+                .buttons
+                .get(player)
+                .map_or(NotPressed, |buttons| buttons[index]),
+            None if player == 0 => self.mouse_pressed,
+            None => NotPressed,
         }
     }
 
     pub(crate) fn mouse(&self) -> (i32, i32) {
         (self.mouse_x, self.mouse_y)
     }
+
+    pub(crate) fn btnp_repeat(&self) -> BtnpRepeat {
+        self.btnp_repeat
+    }
+
+    pub(crate) fn set_btnp_repeat(&mut self, btnp_repeat: BtnpRepeat) {
+        self.btnp_repeat = btnp_repeat;
+    }
 }
 
-#[derive(Debug)]
+/// When a held button makes `btnp` true again, like pico8's `0x5f5c` and `0x5f5d` registers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct BtnpRepeat {
+    /// Frames until the first repeat: 0 for the default (15), 255 to never repeat.
+    pub(crate) delay: u8,
+    /// Frames between the following repeats: 0 for the default (4).
+    pub(crate) interval: u8,
+}
+
+impl BtnpRepeat {
+    const DEFAULT_DELAY: u32 = 15;
+    const DEFAULT_INTERVAL: u32 = 4;
+    const NEVER: u8 = 255;
+    /// `btnp` is only true in the frame the button is pressed.
+    const OFF: Self = Self {
+        delay: Self::NEVER,
+        interval: 0,
+    };
+
+    /// Whether `btnp` is true for a button held down `frames` frames after it was pressed.
+    fn repeats_at(self, frames: u32) -> bool {
+        if self.delay == Self::NEVER {
+            return false;
+        }
+
+        let delay = match self.delay {
+            0 => Self::DEFAULT_DELAY,
+            delay => delay as u32,
+        };
+        let interval = match self.interval {
+            0 => Self::DEFAULT_INTERVAL,
+            interval => interval as u32,
+        };
+
+        frames >= delay && (frames - delay).is_multiple_of(interval)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub(crate) enum ButtonState {
     JustPressed, // btn => true, btnp => true
-    Held,        // btn => true, btnp => false
+    /// btn => true, btnp => `repeat`.
+    Held {
+        /// Frames since the button was pressed.
+        frames: u32,
+        /// Whether `btnp` fires again this frame, see [`BtnpRepeat`].
+        repeat: bool,
+    },
     NotPressed,
 }
 
 impl ButtonState {
-    fn update(&mut self, is_pressed: Option<bool>) {
+    fn update(&mut self, is_pressed: Option<bool>, btnp_repeat: BtnpRepeat) {
         match is_pressed {
             Some(is_pressed) => {
                 if is_pressed {
-                    self.press()
+                    self.press(btnp_repeat)
                 } else {
                     self.unpress()
                 }
             }
-            None => self.no_change(),
+            None => self.no_change(btnp_repeat),
         }
     }
 
     // A frame has passed but we've registered no event related to this key.
-    fn no_change(&mut self) {
-        *self = match self {
-            JustPressed => Held,
-            Held => Held,
+    fn no_change(&mut self, btnp_repeat: BtnpRepeat) {
+        *self = match *self {
+            JustPressed => Self::held(1, btnp_repeat),
+            Held { frames, .. } => Self::held(frames + 1, btnp_repeat),
             NotPressed => NotPressed,
         }
     }
 
-    // Caution: This may come either from a "first" press or a "repeated" press.
-    // TODO: I think we don't handle repeated presses yet.
-    fn press(&mut self) {
-        *self = match self {
-            JustPressed => Held,
-            Held => Held,
-            NotPressed => JustPressed,
+    // Caution: This may come either from a "first" press or from the OS repeating the key
+    // while it's held down, which doesn't restart the count.
+    fn press(&mut self, btnp_repeat: BtnpRepeat) {
+        match self {
+            NotPressed => *self = JustPressed,
+            JustPressed | Held { .. } => self.no_change(btnp_repeat),
+        }
+    }
+
+    fn held(frames: u32, btnp_repeat: BtnpRepeat) -> Self {
+        Held {
+            frames,
+            repeat: btnp_repeat.repeats_at(frames),
         }
     }
 
@@ -112,12 +274,310 @@ impl ButtonState {
     pub(crate) fn btn(&self) -> bool {
         match *self {
             JustPressed => true,
-            Held => true,
+            Held { .. } => true,
             NotPressed => false,
         }
     }
 
     pub(crate) fn btnp(&self) -> bool {
-        matches!(*self, JustPressed)
+        matches!(*self, JustPressed | Held { repeat: true, .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BtnpRepeat, State};
+    use crate::{
+        Button, Input, InputEvent, Key, KeyBindings, KeyState, KeyboardEvent, MouseButton,
+        MouseEvent,
+    };
+
+    const STEP: f64 = 1000.0 / 30.0;
+
+    fn press(input: &mut Input, key: Key) {
+        input.on_event(InputEvent::Keyboard(KeyboardEvent {
+            key,
+            state: KeyState::Down,
+        }));
+    }
+
+    fn release(input: &mut Input, key: Key) {
+        input.on_event(InputEvent::Keyboard(KeyboardEvent {
+            key,
+            state: KeyState::Up,
+        }));
+    }
+
+    #[test]
+    fn time_advances_by_the_fixed_step() {
+        let mut state = State::new();
+        assert_eq!(state.time(), 0.0);
+
+        for _ in 0..45 {
+            state.advance_time(STEP);
+        }
+        assert!((state.time() - 1.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn fps_counts_the_frames_of_the_last_second() {
+        let mut state = State::new();
+
+        // Real time goes by in 50ms ticks, only 20 frames fit in a second.
+        for _ in 0..19 {
+            state.advance_time(STEP);
+            state.on_tick(50.0);
+        }
+        assert_eq!(state.fps(), 30, "not measured until a second has passed");
+
+        state.advance_time(STEP);
+        state.on_tick(50.0);
+        assert_eq!(state.fps(), 20);
+        assert_eq!(state.target_fps(), 30);
+    }
+
+    #[test]
+    fn cpu_is_the_fraction_of_the_frame_spent_running_the_game() {
+        let mut state = State::new();
+        state.add_busy_time(STEP / 4.0);
+        state.add_busy_time(STEP / 4.0);
+        state.advance_time(STEP);
+        assert!((state.cpu() - 0.5).abs() < 0.0001);
+
+        state.advance_time(STEP);
+        assert_eq!(state.cpu(), 0.0);
+    }
+
+    #[test]
+    fn typed_characters_are_read_in_order() {
+        let mut state = State::new();
+        let mut input = Input::new();
+        press(&mut input, Key::H);
+        input.on_text('H');
+        input.on_text(' ');
+        press(&mut input, Key::LeftArrow);
+        state.update_input(&mut input);
+
+        assert!(state.has_typed_char());
+        assert_eq!(state.next_typed_char(), Some('H'));
+        assert_eq!(state.next_typed_char(), Some(' '));
+        assert!(!state.has_typed_char(), "only text events type characters");
+
+        state.update_input(&mut input);
+        assert_eq!(
+            state.next_typed_char(),
+            None,
+            "characters are only read once"
+        );
+    }
+
+    #[test]
+    fn control_characters_are_typed_like_pico8() {
+        let mut state = State::new();
+        let mut input = Input::new();
+        for character in ['\n', '\u{8}', '\t', '\u{1b}', '\u{7f}', 'é'] {
+            input.on_text(character);
+        }
+        state.update_input(&mut input);
+
+        let typed = std::iter::from_fn(|| state.next_typed_char()).collect::<String>();
+        assert_eq!(typed, "\r\u{8}\té");
+    }
+
+    #[test]
+    fn each_player_has_their_own_buttons() {
+        let mut state = State::new();
+        let mut input = Input::new();
+        press(&mut input, Key::LeftArrow);
+        press(&mut input, Key::F);
+        press(&mut input, Key::Tab);
+        state.update_input(&mut input);
+
+        assert!(state.button(Button::Left, 0).btnp());
+        assert!(!state.button(Button::Right, 0).btn());
+        assert!(state.button(Button::Right, 1).btnp());
+        assert!(state.button(Button::Circle, 1).btnp());
+        assert!(!state.button(Button::Left, 1).btn());
+
+        release(&mut input, Key::F);
+        state.update_input(&mut input);
+        assert!(state.button(Button::Left, 0).btn());
+        assert!(!state.button(Button::Left, 0).btnp());
+        assert!(!state.button(Button::Right, 1).btn());
+    }
+
+    #[test]
+    fn buttons_follow_the_key_bindings() {
+        let mut key_bindings = KeyBindings::empty();
+        key_bindings
+            .bind(Key::W, 0, Button::Up)
+            .bind(Key::Space, 0, Button::Up);
+        let mut state = State::new();
+        let mut input = Input::with_key_bindings(key_bindings);
+
+        press(&mut input, Key::UpArrow);
+        state.update_input(&mut input);
+        assert!(!state.button(Button::Up, 0).btn());
+
+        press(&mut input, Key::Space);
+        state.update_input(&mut input);
+        assert!(state.button(Button::Up, 0).btnp());
+
+        press(&mut input, Key::W);
+        release(&mut input, Key::Space);
+        state.update_input(&mut input);
+        assert!(
+            state.button(Button::Up, 0).btn(),
+            "still held down with another key"
+        );
+
+        release(&mut input, Key::W);
+        state.update_input(&mut input);
+        assert!(!state.button(Button::Up, 0).btn());
+    }
+
+    /// Frames, counting from 0 when the key is pressed, in which `btnp` is true while
+    /// holding it down for `frames` frames.
+    fn btnp_frames(state: &mut State, frames: u32) -> Vec<u32> {
+        let mut input = Input::new();
+        press(&mut input, Key::X);
+
+        let btnp_frames = (0..frames)
+            .filter(|_| {
+                state.update_input(&mut input);
+                state.button(Button::Cross, 0).btnp()
+            })
+            .collect::<Vec<_>>();
+
+        release(&mut input, Key::X);
+        state.update_input(&mut input);
+
+        btnp_frames
+    }
+
+    #[test]
+    fn btnp_repeats_like_pico8() {
+        let mut state = State::new();
+
+        assert_eq!(btnp_frames(&mut state, 30), [0, 15, 19, 23, 27]);
+    }
+
+    #[test]
+    fn btnp_repeat_is_restarted_by_releasing_the_button() {
+        let mut state = State::new();
+        btnp_frames(&mut state, 10);
+
+        assert_eq!(btnp_frames(&mut state, 16), [0, 15]);
+    }
+
+    #[test]
+    fn held_buttons_repeated_by_the_os_dont_restart_the_count() {
+        let mut state = State::new();
+        let mut input = Input::new();
+        press(&mut input, Key::X);
+        state.update_input(&mut input);
+
+        for _ in 0..14 {
+            press(&mut input, Key::X);
+            state.update_input(&mut input);
+            assert!(!state.button(Button::Cross, 0).btnp());
+        }
+
+        press(&mut input, Key::X);
+        state.update_input(&mut input);
+        assert!(state.button(Button::Cross, 0).btnp());
+    }
+
+    #[test]
+    fn btnp_repeat_is_configurable() {
+        let mut state = State::new();
+
+        state.set_btnp_repeat(BtnpRepeat {
+            delay: 5,
+            interval: 2,
+        });
+        assert_eq!(btnp_frames(&mut state, 10), [0, 5, 7, 9]);
+
+        state.set_btnp_repeat(BtnpRepeat {
+            delay: 5,
+            interval: 0,
+        });
+        assert_eq!(btnp_frames(&mut state, 10), [0, 5, 9]);
+
+        state.set_btnp_repeat(BtnpRepeat {
+            delay: 255,
+            interval: 1,
+        });
+        assert_eq!(btnp_frames(&mut state, 300), [0]);
+    }
+
+    fn mouse(input: &mut Input, event: MouseEvent) {
+        input.on_event(InputEvent::Mouse(event));
+    }
+
+    #[test]
+    fn every_mouse_button_is_tracked() {
+        let mut state = State::new();
+        let mut input = Input::new();
+        let button = |button, state| MouseEvent::Button { button, state };
+
+        mouse(&mut input, button(MouseButton::Right, KeyState::Down));
+        mouse(&mut input, button(MouseButton::Middle, KeyState::Down));
+        state.update_input(&mut input);
+        assert_eq!(state.mouse_buttons(), 0b110);
+
+        mouse(&mut input, button(MouseButton::Left, KeyState::Down));
+        mouse(&mut input, button(MouseButton::Middle, KeyState::Up));
+        state.update_input(&mut input);
+        assert_eq!(state.mouse_buttons(), 0b011);
+    }
+
+    #[test]
+    fn held_mouse_buttons_dont_repeat_btnp() {
+        let mut state = State::new();
+        let mut input = Input::new();
+        mouse(
+            &mut input,
+            MouseEvent::Button {
+                button: MouseButton::Left,
+                state: KeyState::Down,
+            },
+        );
+
+        let btnp_frames = (0..30)
+            .filter(|_| {
+                state.update_input(&mut input);
+                state.button(Button::Mouse, 0).btnp()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(btnp_frames, [0]);
+        assert!(state.button(Button::Mouse, 0).btn());
+    }
+
+    #[test]
+    fn wheel_steps_are_counted_per_frame() {
+        let mut state = State::new();
+        let mut input = Input::new();
+
+        mouse(&mut input, MouseEvent::Wheel { delta: 1 });
+        mouse(&mut input, MouseEvent::Wheel { delta: 2 });
+        state.update_input(&mut input);
+        assert_eq!(state.mouse_wheel(), 3);
+
+        mouse(&mut input, MouseEvent::Wheel { delta: -1 });
+        state.update_input(&mut input);
+        assert_eq!(state.mouse_wheel(), -1);
+
+        state.update_input(&mut input);
+        assert_eq!(state.mouse_wheel(), 0);
+    }
+
+    #[test]
+    fn missing_players_never_press_buttons() {
+        let state = State::new();
+
+        assert!(!state.button(Button::Left, 8).btn());
+        assert!(!state.button(Button::Mouse, 1).btn());
     }
 }
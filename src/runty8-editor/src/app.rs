@@ -3,7 +3,7 @@ use crate::ui::Element;
 use crate::Resources;
 use runty8_core::Input;
 use runty8_core::InputEvent;
-use runty8_core::{App, Event, Pico8};
+use runty8_core::{App, Event, FixedStep, KeyBindings, Pico8};
 use std::fmt::Debug;
 
 /// An Elm-style app
@@ -24,7 +24,7 @@ pub(crate) struct ElmAppCompat<A> {
 impl<A: ElmApp> AppCompat for ElmAppCompat<A> {
     type Msg = A::Msg;
 
-    fn init(_: &mut Pico8) -> Self {
+    fn init(_: &mut Pico8, _: &KeyBindings) -> Self {
         Self { app: A::init() }
     }
 
@@ -45,55 +45,70 @@ impl<A: ElmApp> AppCompat for ElmAppCompat<A> {
 pub enum Pico8AppMsg {
     Tick { delta_millis: f64 },
     Input(InputEvent),
+    Text(char),
 }
 
 pub(crate) struct Pico8AppCompat<A> {
     app: A,
     keys: Input,
-    accumulated_delta: f64,
-    delta_time: f64,
+    fixed_step: FixedStep,
 }
 
 impl<A: App> AppCompat for Pico8AppCompat<A> {
     type Msg = Pico8AppMsg;
 
-    fn init(pico8: &mut Pico8) -> Self {
-        let fps = 30_f64;
+    fn init(pico8: &mut Pico8, key_bindings: &KeyBindings) -> Self {
+        pico8.state.set_target_fps(A::FPS);
 
         Self {
             app: A::init(pico8),
-            keys: Input::new(),
-            accumulated_delta: 0.0,
-            delta_time: 1000.0 / fps,
+            keys: Input::with_key_bindings(key_bindings.clone()),
+            fixed_step: FixedStep::new(A::FPS),
         }
     }
 
     fn update(&mut self, msg: &Self::Msg, pico8: &mut Pico8) {
         match *msg {
             Pico8AppMsg::Tick { delta_millis } => {
-                self.accumulated_delta += delta_millis;
+                pico8.state.on_tick(delta_millis);
+                let delta_time = self.fixed_step.step_millis();
 
-                while self.accumulated_delta > self.delta_time {
-                    pico8.state.update_input(&self.keys);
+                for _ in 0..self.fixed_step.advance(delta_millis) {
+                    pico8.state.update_input(&mut self.keys);
+
+                    let update_start = instant::now();
                     self.app.update(pico8);
-                    self.accumulated_delta -= self.delta_time;
+                    pico8.render_audio(delta_time);
+                    pico8.state.add_busy_time(instant::now() - update_start);
+                    // `draw` runs in `view`, its time counts towards the next frame.
+                    pico8.state.advance_time(delta_time);
                 }
             }
 
             Pico8AppMsg::Input(event) => {
                 self.keys.on_event(event);
             }
+
+            Pico8AppMsg::Text(character) => {
+                self.keys.on_text(character);
+            }
         }
     }
 
     fn view(&mut self, _: &mut Resources) -> Element<'_, Self::Msg> {
-        DrawFn::new(|draw| self.app.draw(draw)).into()
+        DrawFn::new(|pico8| {
+            let draw_start = instant::now();
+            self.app.draw(pico8);
+            pico8.state.add_busy_time(instant::now() - draw_start);
+        })
+        .into()
     }
 
     fn subscriptions(&self, event: &Event) -> Vec<Self::Msg> {
         match *event {
             Event::Input(input_event) => vec![Pico8AppMsg::Input(input_event)],
             Event::Tick { delta_millis } => vec![Pico8AppMsg::Tick { delta_millis }],
+            Event::Text(character) => vec![Pico8AppMsg::Text(character)],
             Event::WindowClosed => vec![],
         }
     }
@@ -102,8 +117,53 @@ impl<A: App> AppCompat for Pico8AppCompat<A> {
 /// Not intended for direct use.
 pub(crate) trait AppCompat {
     type Msg: Copy + Debug;
-    fn init(pico8: &mut Pico8) -> Self;
+    fn init(pico8: &mut Pico8, key_bindings: &KeyBindings) -> Self;
     fn update(&mut self, msg: &Self::Msg, pico8: &mut Pico8);
     fn view(&mut self, resources: &mut Resources) -> Element<'_, Self::Msg>;
     fn subscriptions(&self, event: &Event) -> Vec<Self::Msg>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AppCompat, Pico8AppCompat, Pico8AppMsg};
+    use runty8_core::{App, KeyBindings, Pico8, Resources};
+
+    struct Counter<const FPS: u32> {
+        updates: u32,
+    }
+
+    impl<const FPS: u32> App for Counter<FPS> {
+        const FPS: u32 = FPS;
+
+        fn init(_: &mut Pico8) -> Self {
+            Self { updates: 0 }
+        }
+
+        fn update(&mut self, _: &mut Pico8) {
+            self.updates += 1;
+        }
+
+        fn draw(&mut self, _: &mut Pico8) {}
+    }
+
+    /// Updates during a simulated second, ticking at 144Hz.
+    fn updates_in_a_second<const FPS: u32>() -> u32 {
+        let mut pico8 = Pico8::new(Resources::empty());
+        let mut compat = Pico8AppCompat::<Counter<FPS>>::init(&mut pico8, &KeyBindings::default());
+
+        for _ in 0..144 {
+            let tick = Pico8AppMsg::Tick {
+                delta_millis: 1000.0 / 144.0,
+            };
+            compat.update(&tick, &mut pico8);
+        }
+
+        compat.app.updates
+    }
+
+    #[test]
+    fn honors_the_apps_frame_rate() {
+        assert_eq!(updates_in_a_second::<30>(), 30);
+        assert_eq!(updates_in_a_second::<60>(), 60);
+    }
+}
@@ -8,7 +8,9 @@ use crate::{
     ui::Element,
     Resources,
 };
-use runty8_core::{DrawData, Event, InputEvent, Key, KeyboardEvent, MouseEvent, Pico8};
+use runty8_core::{
+    DrawData, Event, InputEvent, Key, KeyBindings, KeyboardEvent, MouseEvent, Pico8,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum Msg<AppMsg> {
@@ -31,6 +33,8 @@ pub(crate) struct Controller<Game> {
     editor: Editor,
     app: Game,
     key_combos: KeyCombos<KeyComboAction>,
+    /// Kept to restart the game with the same controls.
+    key_bindings: KeyBindings,
     pico8: Pico8,
     mouse_position: Vec2i,
     /// The editor and the game can modify the "draw state" (`draw_data`): camera, palette, etc.
@@ -40,8 +44,8 @@ pub(crate) struct Controller<Game> {
 }
 
 impl<T> Controller<T> {
-    pub(crate) fn screen_buffer(&self) -> &[u8] {
-        self.pico8.draw_data.buffer()
+    pub(crate) fn draw_data(&self) -> &DrawData {
+        &self.pico8.draw_data
     }
 
     pub(crate) fn take_new_title(&mut self) -> Option<String> {
@@ -50,16 +54,17 @@ impl<T> Controller<T> {
 }
 
 impl<Game: AppCompat> Controller<Game> {
-    pub fn init(scene: Scene, resources: Resources) -> Self {
+    pub fn init(scene: Scene, resources: Resources, key_bindings: KeyBindings) -> Self {
         let mut pico8 = Pico8::new(resources);
 
         Self {
             scene,
             editor: <Editor as ElmApp>::init(),
-            app: Game::init(&mut pico8),
+            app: Game::init(&mut pico8, &key_bindings),
             key_combos: KeyCombos::new()
                 .push(KeyComboAction::RestartGame, Key::R, &[Key::Control])
                 .push(KeyComboAction::SwitchScene, Key::Escape, &[]),
+            key_bindings,
             pico8,
             mouse_position: Vec2i::new(64, 64),
             alternate_draw_data: DrawData::new(),
@@ -87,7 +92,7 @@ impl<Game: AppCompat> Controller<Game> {
             &Msg::MouseEvent(MouseEvent::Move { x, y }) => {
                 self.mouse_position = Vec2i::new(x, y);
             }
-            &Msg::MouseEvent(MouseEvent::Button { .. }) => {}
+            &Msg::MouseEvent(MouseEvent::Button { .. } | MouseEvent::Wheel { .. }) => {}
             &Msg::Tick => {}
         }
     }
@@ -111,6 +116,7 @@ impl<Game: AppCompat> Controller<Game> {
                 Some(Msg::KeyboardEvent(*keyboard_event))
             }
             Event::Tick { .. } => Some(Msg::Tick),
+            Event::Text(_) => None,
             Event::WindowClosed => todo!("WindowClosed event not yet handled"),
         }
         .into_iter();
@@ -136,7 +142,7 @@ impl<Game: AppCompat> Controller<Game> {
     fn handle_key_combos(&mut self, key_event: KeyboardEvent) {
         self.key_combos.on_event(key_event, |action| match action {
             KeyComboAction::RestartGame => {
-                self.app = Game::init(&mut self.pico8);
+                self.app = Game::init(&mut self.pico8, &self.key_bindings);
                 self.scene = Scene::App;
             }
             KeyComboAction::SwitchScene => {
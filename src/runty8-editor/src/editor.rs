@@ -167,18 +167,18 @@ fn handle_key_combo(
     match key_combo {
         KeyComboAction::Copy => {
             let sprite = resources.sprite_sheet.get_sprite(selected_sprite);
-            notification.alert("COPIED 1 X 1 SPRITES".to_owned());
+            notification.alert("copied 1 x 1 sprites".to_owned());
             clipboard.copy_sprite(sprite);
         }
         KeyComboAction::Paste => {
             let sprite = resources.sprite_sheet.get_sprite_mut(selected_sprite);
-            notification.alert("PASTED 1 X 1 SPRITES".to_owned());
+            notification.alert("pasted 1 x 1 sprites".to_owned());
 
             clipboard.paste_into(sprite);
         }
         KeyComboAction::Cut => {
             let sprite = resources.sprite_sheet.get_sprite_mut(selected_sprite);
-            notification.alert("CUT 1 X 1 SPRITES".to_owned());
+            notification.alert("cut 1 x 1 sprites".to_owned());
 
             clipboard.cut_sprite(sprite);
         }
@@ -211,7 +211,7 @@ fn handle_key_combo(
 }
 
 fn save(notification: &mut notification::State, resources: &Resources) {
-    notification.alert("SAVED".to_owned());
+    notification.alert("saved".to_owned());
 
     let to_serialize: &[(&str, &dyn Serialize)] = &[
         (&Flags::file_name(), &resources.sprite_flags),
@@ -326,7 +326,7 @@ impl ElmApp for Editor {
                 self.selected_sprite = *selected_sprite;
             }
             Msg::FlagHovered { bit_number } => {
-                self.bottom_bar_text = format!("FLAG {} (0X{:X})", bit_number, 1 << bit_number);
+                self.bottom_bar_text = format!("flag {} (0x{:x})", bit_number, 1 << bit_number);
             }
             Msg::FlagToggled(flag_index) => {
                 let flag_index = *flag_index;
@@ -380,20 +380,26 @@ impl ElmApp for Editor {
                     .for_each(|tb| tb.update(selected_tool));
             }
             &Msg::ColorHovered(color) => {
-                self.bottom_bar_text = format!("COLOUR {color}");
+                self.bottom_bar_text =
+                    format!("colour {}", self.sprite_editor.display_color(color));
             }
 
             &Msg::ClickedMapTile { x, y } => {
-                resources.map.mset(x, y, self.selected_sprite as u8);
+                resources.map.mset(
+                    &mut resources.sprite_sheet,
+                    x,
+                    y,
+                    self.selected_sprite as u8,
+                );
             }
             &Msg::BrushSizeSelected(brush_size) => {
                 self.brush_size = brush_size;
                 self.bottom_bar_text =
-                    format!("BRUSH SIZE: {}", self.brush_size.to_human_readable());
+                    format!("brush size: {}", self.brush_size.to_human_readable());
             }
             &Msg::BrushSizeSliderHovered => {
                 self.bottom_bar_text =
-                    format!("BRUSH SIZE: {}", self.brush_size.to_human_readable());
+                    format!("brush size: {}", self.brush_size.to_human_readable());
             }
             #[cfg(target_arch = "wasm32")]
             &Msg::ExportWebAssets => {
@@ -401,7 +407,7 @@ impl ElmApp for Editor {
             }
             #[cfg(target_arch = "wasm32")]
             Msg::ExportWebAssetsHovered => {
-                self.bottom_bar_text = "EXPORT ASSETS".to_owned();
+                self.bottom_bar_text = "export assets".to_owned();
             }
         }
     }
@@ -409,8 +415,20 @@ impl ElmApp for Editor {
     fn view(&mut self, resources: &Resources) -> Element<'_, Msg> {
         const BACKGROUND: u8 = 5;
 
+        let display_palette: Vec<Color> = (0..16)
+            .map(|color| match self.tab {
+                Tab::SpriteEditor => self.sprite_editor.display_color(color),
+                Tab::MapEditor => color,
+            })
+            .collect();
+
         Tree::new()
-            .push(DrawFn::new(|draw| {
+            .push(DrawFn::new(move |draw| {
+                for (color, &display_color) in display_palette.iter().enumerate() {
+                    draw.pal_(color as Color, display_color, 1);
+                }
+                draw.draw_data
+                    .screen_palette_areas(&sprite::Editor::PALETTE_AREAS);
                 draw.rectfill(0, 0, 127, 127, BACKGROUND)
             }))
             .push(self.top_bar.view(self.tab))
@@ -431,6 +449,7 @@ impl ElmApp for Editor {
                 Tab::MapEditor => Tree::new()
                     .push(self.map_editor.view(
                         &resources.map,
+                        &resources.sprite_sheet,
                         0,
                         8,
                         &|x, y| Msg::ClickedMapTile { x, y },
@@ -2,7 +2,7 @@ use crate::ui::button::{self, Button};
 use crate::ui::{DrawFn, Element, Tree};
 use crate::util::vec2::{vec2, Vec2i};
 use itertools::Itertools;
-use runty8_core::{Event, InputEvent, Key, KeyState, KeyboardEvent, Map, MouseEvent};
+use runty8_core::{Event, InputEvent, Key, KeyState, KeyboardEvent, Map, MouseEvent, SpriteSheet};
 use std::fmt::Debug;
 
 #[derive(Debug)]
@@ -73,6 +73,7 @@ impl Editor {
     pub(crate) fn view<'a, 'b, Msg: Copy + Debug + 'a>(
         &'a mut self,
         map: &'b Map,
+        sprite_sheet: &'b SpriteSheet,
         x: i32,
         y: i32,
         on_tile_click: &impl Fn(usize, usize) -> Msg,
@@ -92,7 +93,7 @@ impl Editor {
             .enumerate()
             .flat_map(|(row_index, row)| {
                 row.into_iter().enumerate().map(move |(col_index, state)| {
-                    let sprite = map.mget(col_index as i32, row_index as i32);
+                    let sprite = map.mget(sprite_sheet, col_index as i32, row_index as i32);
 
                     let Vec2i { x, y } = tile_position(camera, col_index, row_index) + vec2(x, y);
                     Button::new(
@@ -107,7 +108,7 @@ impl Editor {
                             if show_sprites_in_map {
                                 draw.spr(sprite.into(), 0, 0);
                             } else {
-                                draw.print(&format!("{sprite:0>2X}"), 0, 1, 7);
+                                draw.print(&format!("{sprite:0>2x}"), 0, 1, 7);
                             }
                         }),
                     )
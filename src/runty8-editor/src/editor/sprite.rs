@@ -5,18 +5,27 @@ use crate::ui::{
     DrawFn, Element, Tree,
 };
 use itertools::Itertools;
-use runty8_core::{Color, Sprite, SpriteSheet};
+use runty8_core::{colors, Color, Sprite, SpriteSheet, EXTENDED_COLORS_START};
 use std::fmt::Debug;
 
+const TOP: i32 = 10;
+const COLOR_SELECTOR_X: i32 = 79;
+const SWATCH_SIZE: i32 = 10;
+const CANVAS_X: i32 = 7;
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum Msg {
     ColorSelected(Color),
+    ExtendedPaletteToggled,
 }
 
 #[derive(Debug)]
 pub(crate) struct Editor {
     selected_color: Color,
+    /// Whether the editor is displayed with pico8's extended palette.
+    extended_palette: bool,
     color_selector_state: Vec<button::State>,
+    extended_palette_button: button::State,
     flag_buttons: Vec<button::State>,
     pixel_buttons: Vec<button::State>,
     brush_size_state: brush_size::State,
@@ -26,7 +35,9 @@ impl Editor {
     pub(crate) fn new() -> Self {
         Self {
             selected_color: 0,
+            extended_palette: false,
             color_selector_state: vec![button::State::new(); 16],
+            extended_palette_button: button::State::new(),
             flag_buttons: vec![button::State::new(); 8],
             pixel_buttons: vec![button::State::new(); Sprite::WIDTH * Sprite::HEIGHT],
             brush_size_state: brush_size::State::new(),
@@ -37,6 +48,30 @@ impl Editor {
             Msg::ColorSelected(selected_color) => {
                 self.selected_color = selected_color;
             }
+            Msg::ExtendedPaletteToggled => {
+                self.extended_palette = !self.extended_palette;
+            }
+        }
+    }
+
+    /// Screen areas showing sprite colors, which the extended palette applies to
+    /// (as `(x, y, w, h)`): the color swatches and the canvas' pixels.
+    pub(crate) const PALETTE_AREAS: [(i32, i32, i32, i32); 2] = [
+        (
+            COLOR_SELECTOR_X + 1,
+            TOP + 1,
+            4 * SWATCH_SIZE,
+            4 * SWATCH_SIZE,
+        ),
+        (CANVAS_X + 1, TOP + 1, 64, 64),
+    ];
+
+    /// Color the screen shows for `color`.
+    pub(crate) fn display_color(&self, color: Color) -> Color {
+        if self.extended_palette {
+            EXTENDED_COLORS_START + color
+        } else {
+            color
         }
     }
 
@@ -50,17 +85,24 @@ impl Editor {
     ) -> Element<'a, super::Msg> {
         Tree::new()
             .push(color_selector(
-                79,
-                10,
-                10,
+                COLOR_SELECTOR_X,
+                TOP,
+                SWATCH_SIZE,
                 self.selected_color,
                 &mut self.color_selector_state,
                 move |color| to_editor_msg(Msg::ColorSelected(color)),
                 super::Msg::ColorHovered,
             ))
+            .push(extended_palette_toggle(
+                121,
+                TOP,
+                self.extended_palette,
+                &mut self.extended_palette_button,
+                to_editor_msg(Msg::ExtendedPaletteToggled),
+            ))
             .push(canvas_view(
-                7,
-                10,
+                CANVAS_X,
+                TOP,
                 self.selected_color,
                 &mut self.pixel_buttons,
                 selected_sprite,
@@ -162,6 +204,40 @@ fn color_selector<'a, Msg: Debug + Copy + 'a>(
     Tree::with_children(v).into()
 }
 
+/// Switches between displaying the regular and the extended palette.
+fn extended_palette_toggle<'a, Msg: Copy + Debug + 'a>(
+    x: i32,
+    y: i32,
+    extended_palette: bool,
+    state: &'a mut button::State,
+    on_press: Msg,
+) -> Element<'a, Msg> {
+    const SIZE: i32 = 5;
+
+    Button::new(
+        x,
+        y,
+        SIZE,
+        SIZE,
+        Some(on_press),
+        state,
+        DrawFn::new(move |draw| {
+            let color = if extended_palette {
+                colors::WHITE
+            } else {
+                colors::LAVENDER
+            };
+
+            draw.rect(0, 0, SIZE - 1, SIZE - 1, color);
+            if extended_palette {
+                draw.rectfill(2, 2, 2, 2, color);
+            }
+        }),
+    )
+    .event_on_press()
+    .into()
+}
+
 fn flags<'a>(
     selected_sprite_flags: u8,
     x: i32,
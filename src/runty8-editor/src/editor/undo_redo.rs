@@ -29,7 +29,7 @@ impl Commands {
 
             command.undo(sprite_sheet);
         } else {
-            notification.alert("NOTHING TO UNDO".to_owned());
+            notification.alert("nothing to undo".to_owned());
         }
     }
 
@@ -40,7 +40,7 @@ impl Commands {
 
             command.redo(sprite_sheet);
         } else {
-            notification.alert("NOTHING TO REDO".to_owned());
+            notification.alert("nothing to redo".to_owned());
         }
     }
 }
@@ -122,7 +122,7 @@ mod tests {
         commands.undo(&mut notification, &mut sprite_sheet);
 
         assert_eq!(commands.current, 0);
-        assert_eq!(notification.content(), "NOTHING TO UNDO");
+        assert_eq!(notification.content(), "nothing to undo");
     }
 
     #[test]
@@ -134,7 +134,7 @@ mod tests {
         commands.redo(&mut notification, &mut sprite_sheet);
 
         assert_eq!(commands.current, 0);
-        assert_eq!(notification.content(), "NOTHING TO REDO");
+        assert_eq!(notification.content(), "nothing to redo");
     }
 
     #[test]
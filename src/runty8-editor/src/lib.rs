@@ -9,7 +9,7 @@ mod app;
 pub mod ui;
 
 pub use app::ElmApp;
-use runty8_core::{App, Resources};
+use runty8_core::{App, KeyBindings, Resources};
 
 mod controller;
 mod editor;
@@ -21,17 +21,28 @@ use controller::Scene;
 
 /// Run a Pico8 application.
 pub fn run_app<T: App + 'static>(resources: Resources) -> std::io::Result<()> {
-    run_app_compat::<Pico8AppCompat<T>>(resources)
+    run_app_with_key_bindings::<T>(resources, KeyBindings::default())
+}
+
+/// Like [`run_app`], controlling the game with `key_bindings` instead of pico8's keys.
+pub fn run_app_with_key_bindings<T: App + 'static>(
+    resources: Resources,
+    key_bindings: KeyBindings,
+) -> std::io::Result<()> {
+    run_app_compat::<Pico8AppCompat<T>>(resources, key_bindings)
 }
 
 /// Run an Elm-style application.
 pub fn run_elm_app<T: ElmApp + 'static>(resources: Resources) -> std::io::Result<()> {
-    run_app_compat::<ElmAppCompat<T>>(resources)
+    run_app_compat::<ElmAppCompat<T>>(resources, KeyBindings::default())
 }
 // TODO: add example
-fn run_app_compat<T: AppCompat + 'static>(resources: Resources) -> std::io::Result<()> {
+fn run_app_compat<T: AppCompat + 'static>(
+    resources: Resources,
+    key_bindings: KeyBindings,
+) -> std::io::Result<()> {
     let starting_scene = start_scene();
-    implementation::run_app::<T>(starting_scene, resources);
+    implementation::run_app::<T>(starting_scene, resources, key_bindings);
 
     Ok(())
 }
@@ -48,14 +59,18 @@ mod implementation {
     use crate::app::AppCompat;
     use crate::controller::{Controller, Scene};
     use crate::Resources;
-    use runty8_core::Event;
+    use runty8_core::{Event, KeyBindings};
     use runty8_winit::ScreenInfo;
 
-    pub(super) fn run_app<Game: AppCompat + 'static>(scene: Scene, resources: Resources) {
+    pub(super) fn run_app<Game: AppCompat + 'static>(
+        scene: Scene,
+        resources: Resources,
+        key_bindings: KeyBindings,
+    ) {
         let mut screen_info = ScreenInfo::new(640.0, 640.0);
         screen_info.scale_factor = 1.0;
 
-        let mut controller = Controller::<Game>::init(scene, resources);
+        let mut controller = Controller::<Game>::init(scene, resources, key_bindings);
 
         runty8_event_loop::event_loop(move |event, control_flow, draw, set_title| {
             controller.step(event);
@@ -65,7 +80,7 @@ mod implementation {
             }
 
             if let Event::Tick { .. } = event {
-                draw(controller.screen_buffer(), control_flow);
+                draw(controller.draw_data(), control_flow);
             }
         });
     }
@@ -3,7 +3,7 @@
 //! Winit/Glow/Glutin powered event loop for Runty8 applications.
 
 use glow::HasContext;
-use runty8_core::Event;
+use runty8_core::{colors, DrawData, Event};
 use runty8_winit::{Runty8EventExt as _, ScreenInfo};
 use winit::{
     event_loop::{ControlFlow, EventLoop},
@@ -20,7 +20,7 @@ type Window = winit::window::Window;
 
 /// Create a window (or canvas, in wasm) and respond to events on it.
 pub fn event_loop(
-    mut on_event: impl FnMut(Event, &mut ControlFlow, &dyn Fn(&[u8], &mut ControlFlow), &dyn Fn(&str))
+    mut on_event: impl FnMut(Event, &mut ControlFlow, &dyn Fn(&DrawData, &mut ControlFlow), &dyn Fn(&str))
         + 'static,
 ) {
     let mut screen_info = ScreenInfo::new(640.0, 640.0);
@@ -59,8 +59,8 @@ pub fn event_loop(
             Event::from_winit(&winit_event, &mut current_time, &mut screen_info);
 
         if let Some(event) = event {
-            let draw: &dyn Fn(&[u8], &mut ControlFlow) = &|pixels, _control_flow| {
-                draw(&gl, texture, pixels);
+            let draw: &dyn Fn(&DrawData, &mut ControlFlow) = &|draw_data, _control_flow| {
+                draw(&gl, texture, &to_rgb(draw_data));
                 #[cfg(not(target_arch = "wasm32"))]
                 window.swap_buffers().unwrap();
             };
@@ -72,6 +72,22 @@ pub fn event_loop(
     })
 }
 
+/// Converts the screen to RGB pixels, applying the screen palette.
+fn to_rgb(draw_data: &DrawData) -> Vec<u8> {
+    draw_data
+        .buffer()
+        .iter()
+        .flat_map(|byte| [byte & 0x0f, byte >> 4])
+        .enumerate()
+        .flat_map(|(index, color)| {
+            let (x, y) = (index % 128, index / 128);
+            let screen_palette = draw_data.screen_palette_at(x as i32, y as i32);
+
+            colors::rgb(screen_palette[color as usize])
+        })
+        .collect()
+}
+
 fn draw(gl: &glow::Context, texture: glow::Texture, pixels: &[u8]) {
     unsafe {
         gl::upload_pixels(gl, texture, pixels);
@@ -197,3 +213,20 @@ mod wasm {
         document.set_title(title);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_rgb_applies_the_screen_palette() {
+        let mut pico8 = runty8_core::Pico8::new(runty8_core::Resources::empty());
+        pico8.pset(1, 0, colors::RED);
+        pico8.pal_(colors::BLACK, colors::WHITE, 1);
+
+        let rgb = to_rgb(&pico8.draw_data);
+
+        assert_eq!(rgb.len(), 128 * 128 * 3);
+        assert_eq!(&rgb[0..6], [0xFF, 0xF1, 0xE8, 0xFF, 0x00, 0x4D]);
+    }
+}
@@ -2,51 +2,201 @@
 
 //! Run a standalone Runty8 game natively or in wasm.
 
-use runty8_core::{App, Event, Input, Pico8, Resources};
+use runty8_core::{App, DrawData, Event, FixedStep, Input, KeyBindings, Pico8, Resources};
 use runty8_event_loop::event_loop;
 use winit::event_loop::ControlFlow;
 
 /// Runs a standalone Runty8 game.
 pub fn run<Game: App + 'static>(resources: Resources) -> std::io::Result<()> {
-    let mut pico8 = Pico8::new(resources);
-    let mut game = Game::init(&mut pico8);
-    let mut input = Input::new();
+    run_with_key_bindings::<Game>(resources, KeyBindings::default())
+}
+
+/// Like [`run`], controlling the game with `key_bindings` instead of pico8's keys.
+pub fn run_with_key_bindings<Game: App + 'static>(
+    resources: Resources,
+    key_bindings: KeyBindings,
+) -> std::io::Result<()> {
+    let mut game_loop = GameLoop::<Game>::new(resources, key_bindings);
+
+    event_loop(move |event, control_flow, draw, set_title| {
+        game_loop.on_event(event, control_flow, draw, set_title)
+    });
+    Ok(())
+}
 
-    const DELTA_TIME: f64 = 1000.0 / 30.0;
+/// Runs [`App::FPS`] frames of a game per second.
+struct GameLoop<Game> {
+    pico8: Pico8,
+    game: Game,
+    input: Input,
+    fixed_step: FixedStep,
+}
+
+impl<Game: App> GameLoop<Game> {
+    fn new(resources: Resources, key_bindings: KeyBindings) -> Self {
+        let mut pico8 = Pico8::new(resources);
+        pico8.state.set_target_fps(Game::FPS);
+        let game = Game::init(&mut pico8);
+
+        Self {
+            pico8,
+            game,
+            input: Input::with_key_bindings(key_bindings),
+            fixed_step: FixedStep::new(Game::FPS),
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        control_flow: &mut ControlFlow,
+        draw: &dyn Fn(&DrawData, &mut ControlFlow),
+        set_title: &dyn Fn(&str),
+    ) {
+        let pico8 = &mut self.pico8;
 
-    let mut accumulated_delta = 0.0;
-    let on_event = move |event,
-                         control_flow: &mut ControlFlow,
-                         draw: &dyn Fn(&[u8], &mut ControlFlow),
-                         set_title: &dyn Fn(&str)| {
         if let Some(new_title) = pico8.take_new_title() {
             set_title(&new_title);
         }
 
         match event {
             Event::Tick { delta_millis } => {
-                accumulated_delta += delta_millis;
-
-                while accumulated_delta > DELTA_TIME {
-                    pico8.state.update_input(&input);
+                pico8.state.on_tick(delta_millis);
+                let delta_time = self.fixed_step.step_millis();
 
-                    game.update(&mut pico8);
-                    game.draw(&mut pico8);
+                for _ in 0..self.fixed_step.advance(delta_millis) {
+                    pico8.state.update_input(&mut self.input);
 
-                    draw(pico8.draw_data.buffer(), control_flow);
+                    let frame_start = instant::now();
+                    self.game.update(pico8);
+                    pico8.render_audio(delta_time);
+                    self.game.draw(pico8);
+                    pico8.state.add_busy_time(instant::now() - frame_start);
+                    pico8.state.advance_time(delta_time);
 
-                    accumulated_delta -= DELTA_TIME;
+                    draw(&pico8.draw_data, control_flow);
                 }
             }
             Event::Input(input_event) => {
-                input.on_event(input_event);
+                self.input.on_event(input_event);
+            }
+            Event::Text(character) => {
+                self.input.on_text(character);
             }
             Event::WindowClosed => {
                 *control_flow = ControlFlow::Exit;
             }
         }
-    };
+    }
+}
 
-    event_loop(on_event);
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::GameLoop;
+    use runty8_core::audio::{AudioOutput, SAMPLE_RATE};
+    use runty8_core::{App, DrawData, Event, KeyBindings, Pico8, Resources};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use winit::event_loop::ControlFlow;
+
+    #[derive(Default)]
+    struct Counter {
+        updates: u32,
+        draws: u32,
+    }
+
+    impl App for Counter {
+        fn init(_: &mut Pico8) -> Self {
+            Self::default()
+        }
+
+        fn update(&mut self, _: &mut Pico8) {
+            self.updates += 1;
+        }
+
+        fn draw(&mut self, _: &mut Pico8) {
+            self.draws += 1;
+        }
+    }
+
+    struct Counter60(Counter);
+
+    impl App for Counter60 {
+        const FPS: u32 = 60;
+
+        fn init(pico8: &mut Pico8) -> Self {
+            Self(Counter::init(pico8))
+        }
+
+        fn update(&mut self, pico8: &mut Pico8) {
+            self.0.update(pico8);
+        }
+
+        fn draw(&mut self, pico8: &mut Pico8) {
+            self.0.draw(pico8);
+        }
+    }
+
+    /// Runs a simulated second, ticking at 144Hz. Returns how many frames were presented.
+    fn run_a_second<Game: App>(game_loop: &mut GameLoop<Game>) -> u32 {
+        let mut control_flow = ControlFlow::Poll;
+        let presented = Cell::new(0);
+        let draw = |_: &DrawData, _: &mut ControlFlow| presented.set(presented.get() + 1);
+
+        for _ in 0..144 {
+            let tick = Event::Tick {
+                delta_millis: 1000.0 / 144.0,
+            };
+            game_loop.on_event(tick, &mut control_flow, &draw, &|_| {});
+        }
+
+        presented.get()
+    }
+
+    #[test]
+    fn runs_at_30_fps_by_default() {
+        let mut game_loop = GameLoop::<Counter>::new(Resources::empty(), KeyBindings::default());
+        let presented = run_a_second(&mut game_loop);
+
+        assert_eq!(game_loop.game.updates, 30);
+        assert_eq!(game_loop.game.draws, 30);
+        assert_eq!(presented, 30);
+    }
+
+    #[test]
+    fn runs_at_60_fps_if_the_app_asks_for_it() {
+        let mut game_loop = GameLoop::<Counter60>::new(Resources::empty(), KeyBindings::default());
+        let presented = run_a_second(&mut game_loop);
+
+        assert_eq!(game_loop.game.0.updates, 60);
+        assert_eq!(game_loop.game.0.draws, 60);
+        assert_eq!(presented, 60);
+        assert_eq!(game_loop.pico8.time(), 1.0);
+    }
+
+    /// Counts the samples written to it.
+    #[derive(Debug)]
+    struct SampleCounter(Rc<Cell<usize>>);
+
+    impl AudioOutput for SampleCounter {
+        fn write(&mut self, samples: &[f32]) -> std::io::Result<()> {
+            self.0.set(self.0.get() + samples.len());
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn a_second_of_frames_renders_a_second_of_audio() {
+        let mut game_loop = GameLoop::<Counter60>::new(Resources::empty(), KeyBindings::default());
+        let samples = Rc::new(Cell::new(0));
+        game_loop
+            .pico8
+            .set_audio_output(Box::new(SampleCounter(samples.clone())));
+
+        run_a_second(&mut game_loop);
+
+        assert_eq!(game_loop.game.0.updates, 60);
+        assert_eq!(samples.get(), SAMPLE_RATE as usize);
+    }
 }
@@ -12,6 +12,9 @@ pub struct ScreenInfo {
     pub scale_factor: f64,
     /// Display resolution in "logical" units.
     pub logical_size: LogicalSize<f64>,
+    /// Whether the left and right shift keys are held. Both press [`Key::Shift`],
+    /// which is only released once neither is.
+    held_shift_keys: [bool; 2],
 }
 
 impl ScreenInfo {
@@ -20,6 +23,7 @@ impl ScreenInfo {
         Self {
             scale_factor: 1.0,
             logical_size: LogicalSize::new(width, height),
+            held_shift_keys: [false; 2],
         }
     }
 }
@@ -67,25 +71,38 @@ impl Runty8EventExt for Event {
                     })))
                 }
                 winit::event::WindowEvent::MouseInput {
-                    button: winit::event::MouseButton::Left,
+                    button,
                     state: input_state,
                     ..
                 } => {
+                    let button = match button {
+                        winit::event::MouseButton::Left => MouseButton::Left,
+                        winit::event::MouseButton::Right => MouseButton::Right,
+                        winit::event::MouseButton::Middle => MouseButton::Middle,
+                        winit::event::MouseButton::Other(_) => return None,
+                    };
                     let mouse_button_state = match input_state {
                         ElementState::Pressed => KeyState::Down,
                         ElementState::Released => KeyState::Up,
                     };
 
                     let mouse_event = MouseEvent::Button {
-                        button: MouseButton::Left,
+                        button,
                         state: mouse_button_state,
                     };
                     Some(Event::Input(InputEvent::Mouse(mouse_event)))
                 }
+                winit::event::WindowEvent::MouseWheel { delta, .. } => {
+                    let delta = wheel_steps(*delta);
+
+                    (delta != 0)
+                        .then_some(Event::Input(InputEvent::Mouse(MouseEvent::Wheel { delta })))
+                }
+                &winit::event::WindowEvent::ReceivedCharacter(character) => {
+                    Some(Event::Text(character))
+                }
                 winit::event::WindowEvent::KeyboardInput { input, .. } => {
-                    KeyboardEvent::from_winit(*input)
-                        .map(InputEvent::Keyboard)
-                        .map(Event::Input)
+                    keyboard_event(*input, screen_info)
                 }
                 _ => None,
             },
@@ -105,6 +122,42 @@ impl Runty8EventExt for Event {
         }
     }
 }
+
+/// Translates a key press or release, keeping [`Key::Shift`] held while either shift key is.
+fn keyboard_event(
+    input: winit::event::KeyboardInput,
+    screen_info: &mut ScreenInfo,
+) -> Option<Event> {
+    let event = KeyboardEvent::from_winit(input)?;
+
+    if event.key == Key::Shift {
+        let side = (input.virtual_keycode == Some(winit::event::VirtualKeyCode::RShift)) as usize;
+        screen_info.held_shift_keys[side] = event.state == KeyState::Down;
+
+        if event.state == KeyState::Up && screen_info.held_shift_keys.contains(&true) {
+            return None;
+        }
+    }
+
+    Some(Event::Input(InputEvent::Keyboard(event)))
+}
+
+/// Steps scrolled by a wheel event, positive when scrolling up.
+/// Any movement counts as at least a step, so slow touchpad scrolls aren't lost.
+fn wheel_steps(delta: winit::event::MouseScrollDelta) -> i32 {
+    use winit::event::MouseScrollDelta;
+
+    // Roughly the height of a line of text.
+    const PIXELS_PER_STEP: f64 = 16.0;
+
+    let steps = match delta {
+        MouseScrollDelta::LineDelta(_, y) => y as f64,
+        MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_STEP,
+    };
+
+    (steps.abs().ceil() * steps.signum()) as i32
+}
+
 trait Runty8KeyboardEventExt: Sized {
     fn from_winit(input: winit::event::KeyboardInput) -> Option<Self>;
 }
@@ -157,14 +210,57 @@ impl Runty8KeyExt for Key {
             VirtualKeyCode::X => Some(Self::X),
             VirtualKeyCode::Y => Some(Self::Y),
             VirtualKeyCode::Z => Some(Self::Z),
-            VirtualKeyCode::LControl => Some(Self::Control),
+            VirtualKeyCode::LControl | VirtualKeyCode::RControl => Some(Self::Control),
             VirtualKeyCode::Left => Some(Self::LeftArrow),
             VirtualKeyCode::Right => Some(Self::RightArrow),
             VirtualKeyCode::Up => Some(Self::UpArrow),
             VirtualKeyCode::Down => Some(Self::DownArrow),
             VirtualKeyCode::Escape => Some(Self::Escape),
-            VirtualKeyCode::LAlt => Some(Self::Alt),
+            VirtualKeyCode::LAlt | VirtualKeyCode::RAlt => Some(Self::Alt),
             VirtualKeyCode::Space => Some(Self::Space),
+            VirtualKeyCode::Tab => Some(Self::Tab),
+            VirtualKeyCode::LShift | VirtualKeyCode::RShift => Some(Self::Shift),
+            VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some(Self::Digit0),
+            VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some(Self::Digit1),
+            VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some(Self::Digit2),
+            VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some(Self::Digit3),
+            VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some(Self::Digit4),
+            VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some(Self::Digit5),
+            VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some(Self::Digit6),
+            VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some(Self::Digit7),
+            VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some(Self::Digit8),
+            VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some(Self::Digit9),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Some(Self::Enter),
+            VirtualKeyCode::Back => Some(Self::Backspace),
+            VirtualKeyCode::Delete => Some(Self::Delete),
+            VirtualKeyCode::Insert => Some(Self::Insert),
+            VirtualKeyCode::Home => Some(Self::Home),
+            VirtualKeyCode::End => Some(Self::End),
+            VirtualKeyCode::PageUp => Some(Self::PageUp),
+            VirtualKeyCode::PageDown => Some(Self::PageDown),
+            VirtualKeyCode::F1 => Some(Self::F1),
+            VirtualKeyCode::F2 => Some(Self::F2),
+            VirtualKeyCode::F3 => Some(Self::F3),
+            VirtualKeyCode::F4 => Some(Self::F4),
+            VirtualKeyCode::F5 => Some(Self::F5),
+            VirtualKeyCode::F6 => Some(Self::F6),
+            VirtualKeyCode::F7 => Some(Self::F7),
+            VirtualKeyCode::F8 => Some(Self::F8),
+            VirtualKeyCode::F9 => Some(Self::F9),
+            VirtualKeyCode::F10 => Some(Self::F10),
+            VirtualKeyCode::F11 => Some(Self::F11),
+            VirtualKeyCode::F12 => Some(Self::F12),
+            VirtualKeyCode::Minus => Some(Self::Minus),
+            VirtualKeyCode::Equals => Some(Self::Equals),
+            VirtualKeyCode::LBracket => Some(Self::LeftBracket),
+            VirtualKeyCode::RBracket => Some(Self::RightBracket),
+            VirtualKeyCode::Backslash => Some(Self::Backslash),
+            VirtualKeyCode::Semicolon => Some(Self::Semicolon),
+            VirtualKeyCode::Apostrophe => Some(Self::Apostrophe),
+            VirtualKeyCode::Grave => Some(Self::Grave),
+            VirtualKeyCode::Comma => Some(Self::Comma),
+            VirtualKeyCode::Period => Some(Self::Period),
+            VirtualKeyCode::Slash => Some(Self::Slash),
             _ => None,
         }
     }
@@ -184,3 +280,53 @@ impl Runty8KeyStateExt for KeyState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{keyboard_event, ScreenInfo};
+    use runty8_core::{Event, InputEvent, Key, KeyState, KeyboardEvent};
+    use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
+
+    #[allow(deprecated)]
+    fn input(key: VirtualKeyCode, state: ElementState) -> KeyboardInput {
+        KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: Default::default(),
+        }
+    }
+
+    /// State of [`Key::Shift`] sent for `input`, if any.
+    fn shift_state(input: KeyboardInput, screen_info: &mut ScreenInfo) -> Option<KeyState> {
+        match keyboard_event(input, screen_info)? {
+            Event::Input(InputEvent::Keyboard(KeyboardEvent {
+                key: Key::Shift,
+                state,
+            })) => Some(state),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn shift_is_held_while_either_shift_key_is() {
+        use ElementState::*;
+        use VirtualKeyCode::*;
+
+        let screen_info = &mut ScreenInfo::new(128.0, 128.0);
+
+        assert_eq!(
+            shift_state(input(LShift, Pressed), screen_info),
+            Some(KeyState::Down)
+        );
+        assert_eq!(
+            shift_state(input(RShift, Pressed), screen_info),
+            Some(KeyState::Down)
+        );
+        assert_eq!(shift_state(input(LShift, Released), screen_info), None);
+        assert_eq!(
+            shift_state(input(RShift, Released), screen_info),
+            Some(KeyState::Up)
+        );
+    }
+}
@@ -3,31 +3,48 @@
 //! Entrypoints for all games using runty8.
 
 #[doc(inline)]
-pub use runty8_core::{flr, load_assets, mid, rnd, sin, App, Button, Pico8};
+pub use runty8_core::{
+    abs, atan2, band, bnot, bor, bxor, ceil, cos, flr, load_assets, lshr, max, mid, min, rotl,
+    rotr, sgn, shl, shr, sin, sqrt, App, Button, Fixed, Key, KeyBindings, Pico8, Stat, MAX_PLAYERS,
+};
+
+#[doc(inline)]
+#[allow(deprecated)]
+pub use runty8_core::rnd;
 
 use runty8_core::Resources;
 
 #[doc(inline)]
-pub use runty8_editor::run_app as run_editor;
+pub use runty8_editor::{
+    run_app as run_editor, run_app_with_key_bindings as run_editor_with_key_bindings,
+};
 
 #[doc(inline)]
-pub use runty8_runtime::run;
+pub use runty8_runtime::{run, run_with_key_bindings};
 
 /// Run your game in the Editor in `debug` mode, and in the standalone Runtime in `release`.
 pub fn debug_run<Game: App + 'static>(resources: Resources) -> std::io::Result<()> {
+    debug_run_with_key_bindings::<Game>(resources, KeyBindings::default())
+}
+
+/// Like [`debug_run`], controlling the game with `key_bindings` instead of pico8's keys.
+pub fn debug_run_with_key_bindings<Game: App + 'static>(
+    resources: Resources,
+    key_bindings: KeyBindings,
+) -> std::io::Result<()> {
     let run = {
         #[cfg(debug_assertions)]
         {
             println!("Running editor...");
-            run_editor::<Game>
+            run_editor_with_key_bindings::<Game>
         }
 
         #[cfg(not(debug_assertions))]
         {
             println!("Running runtime...");
-            runty8_runtime::run::<Game>
+            runty8_runtime::run_with_key_bindings::<Game>
         }
     };
 
-    run(resources)
+    run(resources, key_bindings)
 }